@@ -63,47 +63,11 @@ impl Paths {
         Ok(Self::data_dir()?.join("mood.json"))
     }
 
-    /// Get the path to the user's shell history.
+    /// Get the event log written by the `tty-mood init` shell hooks.
     ///
-    /// Attempts to detect the current shell and find its history file.
-    /// Supports: bash, zsh, fish
-    ///
-    /// Returns the path if found, None if undetectable.
-    pub fn shell_history() -> Option<PathBuf> {
-        // Try to detect shell from SHELL environment variable
-        let shell = std::env::var("SHELL").ok()?;
-
-        let home = dirs::home_dir()?;
-
-        if shell.contains("zsh") {
-            let path = home.join(".zsh_history");
-            if path.exists() {
-                return Some(path);
-            }
-            // Try ZDOTDIR location
-            if let Ok(zdotdir) = std::env::var("ZDOTDIR") {
-                let path = PathBuf::from(zdotdir).join(".zsh_history");
-                if path.exists() {
-                    return Some(path);
-                }
-            }
-        }
-
-        if shell.contains("bash") {
-            let path = home.join(".bash_history");
-            if path.exists() {
-                return Some(path);
-            }
-        }
-
-        if shell.contains("fish") {
-            let path = home.join(".local/share/fish/fish_history");
-            if path.exists() {
-                return Some(path);
-            }
-        }
-
-        None
+    /// Returns: `~/.local/share/absurdtty/events.log`
+    pub fn event_log() -> Result<PathBuf> {
+        Ok(Self::data_dir()?.join("events.log"))
     }
 
     /// Resolve a path, expanding `~` to home directory.
@@ -343,12 +307,6 @@ mod tests {
         }
     }
 
-    #[test]
-    fn shell_history_detection() {
-        // This test just ensures the function doesn't panic
-        let _ = Paths::shell_history();
-    }
-
     #[test]
     fn atomic_write_creates_file() {
         let temp_dir = env::temp_dir();
@@ -1,6 +1,8 @@
 //! CLI definition for tty-mood.
 
-use clap::{Parser, Subcommand};
+use crate::history::HistoryFormat;
+use crate::hook::HookShell;
+use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -23,19 +25,21 @@ pub struct Cli {
     /// Output format: text, json
     #[arg(long, global = true, default_value = "text")]
     pub format: OutputFormat,
+
+    /// Time zone for temporal signals, e.g. Europe/Berlin (default: system zone)
+    #[arg(long, global = true)]
+    pub tz: Option<String>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Generate a mood signature from shell history
     Generate {
-        /// Time range to analyze (e.g., 7d, 14d, 30d)
-        #[arg(long, default_value = "7d")]
-        range: String,
+        #[command(flatten)]
+        range: RangeArgs,
 
-        /// Path to shell history file (auto-detected if not specified)
-        #[arg(long)]
-        history: Option<PathBuf>,
+        #[command(flatten)]
+        source: SourceArgs,
 
         /// Output file path (default: ~/.local/share/absurdtty/mood.json)
         #[arg(long, short)]
@@ -44,6 +48,14 @@ pub enum Commands {
         /// Print output instead of writing to file
         #[arg(long)]
         dry_run: bool,
+
+        /// Ignore the saved history cursor and re-read everything
+        #[arg(long)]
+        full: bool,
+
+        /// Report how many entries each ignore rule removed (implies --full)
+        #[arg(long)]
+        explain_ignored: bool,
     },
 
     /// Show current mood signature
@@ -55,18 +67,71 @@ pub enum Commands {
 
     /// List all detected signals from current analysis
     Signals {
-        /// Time range to analyze
-        #[arg(long, default_value = "7d")]
-        range: String,
+        #[command(flatten)]
+        range: RangeArgs,
 
-        /// Path to shell history file
-        #[arg(long)]
-        history: Option<PathBuf>,
+        #[command(flatten)]
+        source: SourceArgs,
 
         /// Show all signals (including weak ones)
         #[arg(long)]
         all: bool,
     },
+
+    /// Split history into sessions and show the mood of each
+    Sessions {
+        #[command(flatten)]
+        range: RangeArgs,
+
+        #[command(flatten)]
+        source: SourceArgs,
+
+        /// Minutes without a command that end a session
+        #[arg(long, default_value = "30")]
+        idle: u32,
+    },
+
+    /// Print a shell hook that logs exit status and duration of each command
+    ///
+    /// Add `eval "$(tty-mood init zsh)"` to ~/.zshrc (or bash, in ~/.bashrc),
+    /// or `tty-mood init fish | source` to config.fish.
+    Init {
+        /// Shell to print the hook for: zsh, bash, fish
+        shell: HookShell,
+    },
+}
+
+/// Which stretch of history to analyze.
+#[derive(Args, Debug, Clone)]
+pub struct RangeArgs {
+    /// Time range to analyze: 12h, 7d, 2w, today, yesterday, this-week, last-month
+    #[arg(long, default_value = "7d")]
+    pub range: RangeSpec,
+
+    /// Start of an absolute range: YYYY-MM-DD or YYYY-MM-DDTHH:MM, local time
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// End of the range; a bare date includes that whole day
+    #[arg(long)]
+    pub until: Option<String>,
+}
+
+/// Where history (and the aliases used in it) come from.
+#[derive(Args, Debug, Clone)]
+pub struct SourceArgs {
+    /// Path to shell history file, or `-` for stdin (repeatable; auto-detected if not specified)
+    #[arg(long)]
+    pub history: Vec<PathBuf>,
+
+    /// History format: auto, zsh, bash, fish, atuin, nushell, powershell, asciicast, events
+    #[arg(long, default_value = "auto")]
+    pub history_format: HistoryFormatArg,
+
+    /// rc file, `alias` output or fish functions directory to resolve
+    /// aliases from (repeatable; common rc files if not specified)
+    #[arg(long)]
+    pub aliases: Vec<PathBuf>,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -88,23 +153,105 @@ impl std::str::FromStr for OutputFormat {
     }
 }
 
-/// Parse time range string into days.
-pub fn parse_range(range: &str) -> anyhow::Result<u32> {
-    let range = range.trim().to_lowercase();
-
-    if let Some(days) = range.strip_suffix('d') {
-        days.parse::<u32>()
-            .map_err(|_| anyhow::anyhow!("Invalid range: {}", range))
-    } else if let Some(weeks) = range.strip_suffix('w') {
-        let w = weeks
-            .parse::<u32>()
-            .map_err(|_| anyhow::anyhow!("Invalid range: {}", range))?;
-        Ok(w * 7)
-    } else {
-        // Try parsing as plain number (days)
-        range
-            .parse::<u32>()
-            .map_err(|_| anyhow::anyhow!("Invalid range: {}. Use format like '7d' or '2w'.", range))
+/// History format selection: sniffed automatically or fixed by the user.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HistoryFormatArg {
+    #[default]
+    Auto,
+    Fixed(HistoryFormat),
+}
+
+impl HistoryFormatArg {
+    /// The user-chosen format, if not automatic.
+    pub fn fixed(self) -> Option<HistoryFormat> {
+        match self {
+            HistoryFormatArg::Auto => None,
+            HistoryFormatArg::Fixed(format) => Some(format),
+        }
+    }
+}
+
+impl std::str::FromStr for HistoryFormatArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            Ok(HistoryFormatArg::Auto)
+        } else {
+            s.parse().map(HistoryFormatArg::Fixed)
+        }
+    }
+}
+
+/// A relative or named time range, as given to `--range`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeSpec {
+    /// The last N hours
+    Hours(u32),
+    /// The last N days
+    Days(u32),
+    /// Since local midnight
+    Today,
+    /// The previous local calendar day
+    Yesterday,
+    /// Since Monday, local midnight
+    ThisWeek,
+    /// The previous calendar month
+    LastMonth,
+}
+
+impl RangeSpec {
+    /// Whether the range follows the calendar rather than counting back.
+    pub fn is_named(self) -> bool {
+        !matches!(self, RangeSpec::Hours(_) | RangeSpec::Days(_))
+    }
+}
+
+impl std::str::FromStr for RangeSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let range = s.trim().to_lowercase();
+        let invalid = || {
+            format!(
+                "Invalid range: {}. Use e.g. '12h', '7d', '2w', 'today', 'yesterday', 'this-week' or 'last-month'.",
+                range
+            )
+        };
+        let count = |n: &str| n.parse::<u32>().map_err(|_| invalid());
+
+        match range.as_str() {
+            "today" => Ok(RangeSpec::Today),
+            "yesterday" => Ok(RangeSpec::Yesterday),
+            "this-week" => Ok(RangeSpec::ThisWeek),
+            "last-month" => Ok(RangeSpec::LastMonth),
+            _ => {
+                if let Some(hours) = range.strip_suffix('h') {
+                    count(hours).map(RangeSpec::Hours)
+                } else if let Some(days) = range.strip_suffix('d') {
+                    count(days).map(RangeSpec::Days)
+                } else if let Some(weeks) = range.strip_suffix('w') {
+                    let weeks = count(weeks)?;
+                    weeks.checked_mul(7).map(RangeSpec::Days).ok_or_else(invalid)
+                } else {
+                    // Plain number (days)
+                    count(&range).map(RangeSpec::Days)
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for RangeSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RangeSpec::Hours(hours) => write!(f, "{}h", hours),
+            RangeSpec::Days(days) => write!(f, "{}d", days),
+            RangeSpec::Today => write!(f, "today"),
+            RangeSpec::Yesterday => write!(f, "yesterday"),
+            RangeSpec::ThisWeek => write!(f, "this-week"),
+            RangeSpec::LastMonth => write!(f, "last-month"),
+        }
     }
 }
 
@@ -112,28 +259,53 @@ pub fn parse_range(range: &str) -> anyhow::Result<u32> {
 mod tests {
     use super::*;
 
+    fn parse_range(range: &str) -> Result<RangeSpec, String> {
+        range.parse()
+    }
+
     #[test]
     fn parse_range_days() {
-        assert_eq!(parse_range("7d").unwrap(), 7);
-        assert_eq!(parse_range("14d").unwrap(), 14);
-        assert_eq!(parse_range("30d").unwrap(), 30);
+        assert_eq!(parse_range("7d").unwrap(), RangeSpec::Days(7));
+        assert_eq!(parse_range("14d").unwrap(), RangeSpec::Days(14));
+        assert_eq!(parse_range("30d").unwrap(), RangeSpec::Days(30));
     }
 
     #[test]
     fn parse_range_weeks() {
-        assert_eq!(parse_range("1w").unwrap(), 7);
-        assert_eq!(parse_range("2w").unwrap(), 14);
+        assert_eq!(parse_range("1w").unwrap(), RangeSpec::Days(7));
+        assert_eq!(parse_range("2w").unwrap(), RangeSpec::Days(14));
     }
 
     #[test]
     fn parse_range_plain_number() {
-        assert_eq!(parse_range("7").unwrap(), 7);
+        assert_eq!(parse_range("7").unwrap(), RangeSpec::Days(7));
+    }
+
+    #[test]
+    fn parse_range_hours_and_names() {
+        assert_eq!(parse_range("12h").unwrap(), RangeSpec::Hours(12));
+        assert_eq!(parse_range("Today").unwrap(), RangeSpec::Today);
+        assert_eq!(parse_range("this-week").unwrap(), RangeSpec::ThisWeek);
+        assert!(parse_range("last-month").unwrap().is_named());
+        assert!(!parse_range("12h").unwrap().is_named());
+        assert_eq!(parse_range("last-month").unwrap().to_string(), "last-month");
+    }
+
+    #[test]
+    fn history_format_arg() {
+        assert_eq!("auto".parse::<HistoryFormatArg>().unwrap(), HistoryFormatArg::Auto);
+        assert_eq!(
+            "fish".parse::<HistoryFormatArg>().unwrap().fixed(),
+            Some(HistoryFormat::Fish)
+        );
+        assert!("ksh".parse::<HistoryFormatArg>().is_err());
     }
 
     #[test]
     fn parse_range_invalid() {
         assert!(parse_range("abc").is_err());
         assert!(parse_range("7x").is_err());
+        assert!(parse_range("700000000w").is_err());
     }
 }
 
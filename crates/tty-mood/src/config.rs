@@ -0,0 +1,188 @@
+//! Optional configuration for tty-mood.
+//!
+//! Read from `~/.config/absurdtty/tty-mood.toml`. Without the file,
+//! everything is auto-detected.
+//!
+//! Example:
+//! ```toml
+//! aliases = ["~/.zshrc", "~/.config/fish/functions"]
+//!
+//! [[history]]
+//! path = "~/.zsh_history"
+//!
+//! [[history]]
+//! path = "~/containers/dev/.bash_history"
+//! format = "bash"
+//!
+//! [ignore]
+//! leading_space = true              # default
+//! commands = ["pass", "gopass*"]    # globs, or "re:" regexes
+//! lines = ["*.ssh/*", "re:(?i)token="]
+//! ```
+
+use crate::history::ignore::IgnoreRules;
+use crate::history::HistoryFormat;
+use absurd_core::Paths;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// Parsed tty-mood configuration.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// History sources to merge (used when `--history` is not given)
+    pub history: Vec<HistorySourceConfig>,
+    /// Alias definition files (used when `--aliases` is not given)
+    pub aliases: Vec<String>,
+    /// Commands and lines to leave out of analysis
+    pub ignore: IgnoreConfig,
+}
+
+/// Ignore rules, applied while parsing.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IgnoreConfig {
+    /// Skip lines typed with a leading space
+    pub leading_space: bool,
+    /// Patterns matched against every invoked command name
+    pub commands: Vec<String>,
+    /// Patterns matched against the full command line
+    pub lines: Vec<String>,
+}
+
+impl Default for IgnoreConfig {
+    fn default() -> Self {
+        Self {
+            leading_space: true,
+            commands: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+}
+
+/// One configured history source.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HistorySourceConfig {
+    /// Path to the history file (`~` is expanded)
+    pub path: String,
+    /// Format name (sniffed from the file if not specified)
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+impl Config {
+    /// Load the configuration from the default location.
+    ///
+    /// A missing file is not an error; it yields the default config.
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+
+        Self::from_toml(&content).with_context(|| format!("Invalid config file: {:?}", path))
+    }
+
+    /// Parse configuration from TOML text.
+    pub fn from_toml(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Configured alias files with `~` expanded.
+    pub fn alias_paths(&self) -> Vec<PathBuf> {
+        self.aliases.iter().map(|p| Paths::expand_tilde(p)).collect()
+    }
+
+    /// Default config file location.
+    pub fn path() -> Result<PathBuf> {
+        Ok(Paths::config_dir()?.join("tty-mood.toml"))
+    }
+}
+
+impl IgnoreConfig {
+    /// Compile the configured patterns.
+    pub fn rules(&self) -> Result<IgnoreRules> {
+        IgnoreRules::new(self.leading_space, &self.commands, &self.lines)
+            .context("Invalid [ignore] configuration")
+    }
+}
+
+impl HistorySourceConfig {
+    /// The source path with `~` expanded.
+    pub fn path(&self) -> PathBuf {
+        Paths::expand_tilde(&self.path)
+    }
+
+    /// The configured format, if any.
+    pub fn format(&self) -> Result<Option<HistoryFormat>> {
+        self.format
+            .as_deref()
+            .map(|f| f.parse().map_err(|e: String| anyhow::anyhow!(e)))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_config_is_default() {
+        let config = Config::from_toml("").unwrap();
+        assert!(config.history.is_empty());
+    }
+
+    #[test]
+    fn history_sources_parse() {
+        let config = Config::from_toml(
+            r#"
+            aliases = ["~/.zshrc"]
+
+            [[history]]
+            path = "~/.zsh_history"
+
+            [[history]]
+            path = "/tmp/container/.bash_history"
+            format = "bash"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.history.len(), 2);
+        assert_eq!(config.alias_paths().len(), 1);
+        assert!(config.history[0].format().unwrap().is_none());
+        assert!(!config.history[0].path().to_string_lossy().contains('~'));
+        assert_eq!(
+            config.history[1].format().unwrap(),
+            Some(HistoryFormat::Bash)
+        );
+    }
+
+    #[test]
+    fn ignore_section_parses() {
+        let config = Config::from_toml("").unwrap();
+        assert!(config.ignore.leading_space);
+
+        let config = Config::from_toml(
+            "[ignore]\nleading_space = false\ncommands = [\"pass\"]\nlines = [\"re:token\"]\n",
+        )
+        .unwrap();
+        assert!(!config.ignore.leading_space);
+        assert!(config.ignore.rules().is_ok());
+
+        let config = Config::from_toml("[ignore]\ncommands = [\"[\"]\n").unwrap();
+        assert!(config.ignore.rules().is_err());
+    }
+
+    #[test]
+    fn unknown_format_is_an_error() {
+        let config = Config::from_toml("[[history]]\npath = \"x\"\nformat = \"tcsh\"\n").unwrap();
+        assert!(config.history[0].format().is_err());
+    }
+}
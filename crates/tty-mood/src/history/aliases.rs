@@ -0,0 +1,535 @@
+//! Alias and function resolution.
+//!
+//! History records what was typed, so `gst` and `k get pods` show up
+//! as short unknown commands and look like typos. Before signals run,
+//! each alias is replaced by the commands it expands to, and shell
+//! functions are recognized as deliberate commands.
+//!
+//! Definitions are read from:
+//! - rc files: `.zshrc`, `.bashrc`, `.bash_aliases`, fish `config.fish`
+//!   (following `source`/`.` of other files)
+//! - fish `functions/` directories, one `name.fish` file per function
+//! - the output of `alias` saved to a file (`name='value'` lines), when
+//!   given with `--aliases` or in the config; in rc files such a line is
+//!   a variable, not an alias
+//!
+//! Files are only read. Nothing about the definitions is persisted.
+
+use super::{entry, lexer, HistoryEntry};
+use absurd_core::Paths;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// How deep `source` lines are followed.
+const MAX_SOURCE_DEPTH: usize = 3;
+
+/// How deep alias chains (`kgp` -> `k get pods` -> `kubectl`) are expanded.
+const MAX_EXPANSION_DEPTH: usize = 8;
+
+/// Alias and function definitions.
+#[derive(Debug, Default)]
+pub struct AliasTable {
+    /// Alias name -> expansion
+    aliases: HashMap<String, String>,
+    /// Names of shell functions
+    functions: HashSet<String>,
+    /// Files already read (to break `source` cycles)
+    visited: HashSet<PathBuf>,
+}
+
+impl AliasTable {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read definitions from the given files and fish function directories.
+    ///
+    /// The files may be `alias` output as well as rc files.
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        let mut table = Self::new();
+
+        for path in paths {
+            if path.is_dir() {
+                table.read_fish_functions(path)?;
+            } else {
+                table.read_file(path, 0, true)?;
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// Read definitions from the usual rc files, skipping missing ones.
+    pub fn discover() -> Self {
+        let mut table = Self::new();
+
+        for path in default_rc_files() {
+            if path.is_file() {
+                let _ = table.read_file(&path, 0, false);
+            }
+        }
+
+        if let Some(functions) = dirs::config_dir().map(|c| c.join("fish/functions")) {
+            if functions.is_dir() {
+                let _ = table.read_fish_functions(&functions);
+            }
+        }
+
+        table
+    }
+
+    /// Number of known aliases and functions.
+    pub fn len(&self) -> usize {
+        self.aliases.len() + self.functions.len()
+    }
+
+    /// Names of all known aliases and functions.
+    pub fn names(&self) -> impl Iterator<Item = String> + '_ {
+        self.aliases.keys().chain(&self.functions).cloned()
+    }
+
+    /// Whether no definitions are known.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Read an rc file, or an `alias` dump if `alias_dump`, following
+    /// `source` lines.
+    fn read_file(&mut self, path: &Path, depth: usize, alias_dump: bool) -> Result<()> {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if !self.visited.insert(canonical) {
+            return Ok(());
+        }
+
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read alias file: {:?}", path))?;
+        let content = String::from_utf8_lossy(&bytes);
+
+        for source in self.parse_rc(&content, alias_dump) {
+            let source = expand_home(&source);
+            if depth < MAX_SOURCE_DEPTH && source.is_file() {
+                // Sourced files are best effort; a broken one shouldn't stop analysis
+                let _ = self.read_file(&source, depth + 1, false);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read every `*.fish` file in a fish functions directory.
+    fn read_fish_functions(&mut self, dir: &Path) -> Result<()> {
+        let entries = std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read fish functions: {:?}", dir))?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "fish") {
+                let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                    continue;
+                };
+                if let Ok(content) = std::fs::read_to_string(&path) {
+                    self.parse_fish_function(&name, &content);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse rc file content, or `alias` output if `alias_dump`.
+    /// Returns the paths of sourced files.
+    pub fn parse_rc(&mut self, content: &str, alias_dump: bool) -> Vec<String> {
+        let mut sources = Vec::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            let words = lexer::split_words(line);
+
+            match words.first().map(String::as_str) {
+                Some("alias") => self.parse_alias(&words[1..]),
+                Some("source" | ".") if words.len() > 1 => sources.push(words[1].clone()),
+                Some(_) => {
+                    if let Some(name) = function_name(line) {
+                        self.functions.insert(name);
+                    } else if alias_dump && words.len() == 1 && !is_env_var(&words[0]) {
+                        // `alias` output without the keyword: `name='value'`
+                        self.parse_alias(&words);
+                    }
+                }
+                None => {}
+            }
+        }
+
+        sources
+    }
+
+    /// Parse the arguments of an `alias` command.
+    fn parse_alias(&mut self, args: &[String]) {
+        let args: Vec<&String> = args.iter().filter(|a| !a.starts_with('-')).collect();
+
+        // fish: `alias name 'expansion'`
+        if let [name, expansion @ ..] = args.as_slice() {
+            if !name.contains('=') && !expansion.is_empty() {
+                let expansion: Vec<&str> = expansion.iter().map(|s| s.as_str()).collect();
+                self.define(name, &expansion.join(" "));
+                return;
+            }
+        }
+
+        // POSIX: `alias a='x' b='y'`
+        for arg in args {
+            if let Some((name, expansion)) = arg.split_once('=') {
+                self.define(name, expansion);
+            }
+        }
+    }
+
+    /// Parse a fish function file.
+    ///
+    /// Functions created by fish's `alias` carry `--wraps` and resolve
+    /// to their target; other functions are just known names.
+    pub fn parse_fish_function(&mut self, name: &str, content: &str) {
+        let header = content
+            .lines()
+            .map(lexer::split_words)
+            .find(|words| words.first().is_some_and(|w| w == "function"));
+
+        let wraps = header.and_then(|words| {
+            let mut words = words.into_iter();
+            while let Some(word) = words.next() {
+                if let Some(target) = word.strip_prefix("--wraps=") {
+                    return Some(target.to_string());
+                }
+                if word == "--wraps" || word == "-w" {
+                    return words.next();
+                }
+            }
+            None
+        });
+
+        match wraps {
+            Some(target) => self.define(name, &target),
+            None => {
+                self.functions.insert(name.to_string());
+            }
+        }
+    }
+
+    /// Record an alias, ignoring malformed names.
+    fn define(&mut self, name: &str, expansion: &str) {
+        if is_name(name) && !expansion.trim().is_empty() {
+            self.aliases
+                .insert(name.to_string(), expansion.trim().to_string());
+        }
+    }
+
+    /// Resolve aliases in all entries.
+    pub fn resolve_all(&self, entries: &mut [HistoryEntry]) {
+        if self.is_empty() {
+            return;
+        }
+
+        for entry in entries {
+            self.resolve(entry);
+        }
+    }
+
+    /// Replace aliases in an entry's commands with what they expand to,
+    /// and recompute its steps from the expansion.
+    ///
+    /// The first alias or function used is recorded in `entry.alias`.
+    pub fn resolve(&self, entry: &mut HistoryEntry) {
+        let is_defined = |command: &String| {
+            self.aliases.contains_key(command) || self.functions.contains(command)
+        };
+        if !entry.commands.iter().any(is_defined) {
+            return;
+        }
+
+        // What was typed after each command, for aliases of a bare tool
+        // (`g push` with `alias g=git`). Cached entries have no line.
+        let arguments = Some(lexer::lex(&entry.full_line).arguments)
+            .filter(|arguments| arguments.len() == entry.commands.len())
+            .unwrap_or_default();
+
+        let mut resolved = Vec::with_capacity(entry.commands.len());
+        let mut used = None;
+
+        for (i, command) in entry.commands.iter().enumerate() {
+            if is_defined(command) {
+                let argument = arguments.get(i).and_then(Option::as_deref);
+                self.expand(command, argument, 0, &mut resolved, &mut used);
+            } else {
+                let step = entry.steps.get(i).copied();
+                let step = step.unwrap_or_else(|| entry::step_hash(command, None));
+                resolved.push((command.clone(), step));
+            }
+        }
+
+        if let Some((first, _)) = resolved.first() {
+            entry.command = first.clone();
+        }
+        (entry.commands, entry.steps) = resolved.into_iter().unzip();
+        entry.alias = used;
+    }
+
+    /// Expand a command into the commands it runs and their steps.
+    fn expand(
+        &self,
+        command: &str,
+        argument: Option<&str>,
+        depth: usize,
+        resolved: &mut Vec<(String, u64)>,
+        used: &mut Option<String>,
+    ) {
+        if let Some(expansion) = self.aliases.get(command) {
+            used.get_or_insert_with(|| command.to_string());
+
+            let lexed = lexer::lex(expansion);
+            if lexed.commands.is_empty() {
+                resolved.push((command.to_string(), entry::step_hash(command, argument)));
+            }
+            let last = lexed.commands.len().saturating_sub(1);
+            for (i, (target, own)) in lexed.commands.iter().zip(&lexed.arguments).enumerate() {
+                // Arguments typed after the alias go to its last command
+                let target_argument = own.as_deref().or(argument.filter(|_| i == last));
+
+                // `alias ls='ls --color'` refers to the real ls
+                if target == command || depth >= MAX_EXPANSION_DEPTH {
+                    let step = entry::step_hash(target, target_argument);
+                    resolved.push((target.clone(), step));
+                } else {
+                    self.expand(target, target_argument, depth + 1, resolved, used);
+                }
+            }
+            return;
+        }
+
+        if self.functions.contains(command) {
+            used.get_or_insert_with(|| command.to_string());
+        }
+        resolved.push((command.to_string(), entry::step_hash(command, argument)));
+    }
+}
+
+/// The name of a function defined on this line, if any.
+///
+/// Recognizes `name() {`, `function name {` and `function name() {`.
+fn function_name(line: &str) -> Option<String> {
+    let (name, keyword) = match line.strip_prefix("function ") {
+        Some(rest) => (rest.trim_start(), true),
+        None => (line, false),
+    };
+
+    let end = name
+        .find(|c: char| c.is_whitespace() || c == '(' || c == '{')
+        .unwrap_or(name.len());
+    let (name, rest) = name.split_at(end);
+
+    let defines = keyword || rest.trim_start().starts_with("()");
+    (defines && is_name(name)).then(|| name.to_string())
+}
+
+/// Whether a string is a plausible alias or function name.
+fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '+' | '@'))
+}
+
+/// Whether a word is an environment variable assignment (`HISTSIZE=1000`).
+fn is_env_var(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        name.chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+    })
+}
+
+/// Expand `~` and `$HOME` at the start of a sourced path.
+fn expand_home(path: &str) -> PathBuf {
+    match path
+        .strip_prefix("$HOME")
+        .or_else(|| path.strip_prefix("${HOME}"))
+    {
+        Some(rest) => Paths::expand_tilde(&format!("~{}", rest)),
+        None => Paths::expand_tilde(path),
+    }
+}
+
+/// rc files searched when no alias files are configured.
+fn default_rc_files() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    if let Some(zdotdir) = std::env::var_os("ZDOTDIR") {
+        paths.push(PathBuf::from(zdotdir).join(".zshrc"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        for name in [
+            ".zshrc",
+            ".zsh_aliases",
+            ".bashrc",
+            ".bash_aliases",
+            ".aliases",
+        ] {
+            paths.push(home.join(name));
+        }
+    }
+    if let Some(config) = dirs::config_dir() {
+        paths.push(config.join("fish/config.fish"));
+    }
+
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(table: &AliasTable, line: &str) -> HistoryEntry {
+        let mut entry =
+            HistoryEntry::from_command_line(line, None, 1).with_full_line(line.to_string());
+        table.resolve(&mut entry);
+        entry
+    }
+
+    #[test]
+    fn zshrc_aliases_resolve() {
+        let mut table = AliasTable::new();
+        table.parse_rc(
+            "export EDITOR=nvim\nHISTSIZE=10000\n\
+             alias gst='git status'\n\
+             alias gco=\"git checkout\" k=kubectl\n\
+             alias -g L='| less'\n",
+            false,
+        );
+
+        let entry = resolved(&table, "gst");
+        assert_eq!(entry.command, "git");
+        assert_eq!(entry.alias.as_deref(), Some("gst"));
+        assert!(!entry.looks_like_typo());
+
+        assert_eq!(resolved(&table, "k get pods").command, "kubectl");
+        assert_eq!(resolved(&table, "gco main").commands, ["git"]);
+        assert_eq!(table.len(), 4);
+    }
+
+    #[test]
+    fn alias_chains_and_self_references() {
+        let mut table = AliasTable::new();
+        table.parse_rc(
+            "alias k=kubectl\nalias kgp='k get pods'\nalias ls='ls --color=auto'\n",
+            false,
+        );
+
+        assert_eq!(resolved(&table, "kgp -w").commands, ["kubectl"]);
+
+        let ls = resolved(&table, "ls -la");
+        assert_eq!(ls.commands, ["ls"]);
+        assert_eq!(ls.alias.as_deref(), Some("ls"));
+    }
+
+    #[test]
+    fn alias_dump_and_fish_syntax() {
+        let mut table = AliasTable::new();
+        table.parse_rc("gst='git status'\nll='ls -l'\n", true);
+        table.parse_rc("alias gp 'git push'\n", false);
+
+        assert_eq!(resolved(&table, "gst").command, "git");
+        assert_eq!(resolved(&table, "ll").command, "ls");
+        assert_eq!(resolved(&table, "gp").command, "git");
+
+        // In an rc file, `name='value'` sets a variable
+        let mut table = AliasTable::new();
+        table.parse_rc("editor='nvim'\nalias v='nvim'\n", false);
+        assert!(resolved(&table, "editor").alias.is_none());
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn functions_are_known_commands() {
+        let mut table = AliasTable::new();
+        table.parse_rc(
+            "mkcd() {\n  mkdir -p \"$1\" && cd \"$1\"\n}\nfunction gcl {\n}\n",
+            false,
+        );
+
+        let entry = resolved(&table, "gcl repo");
+        assert_eq!(entry.command, "gcl");
+        assert_eq!(entry.alias.as_deref(), Some("gcl"));
+        assert!(!entry.looks_like_typo());
+        assert!(resolved(&table, "mkcd x").alias.is_some());
+    }
+
+    #[test]
+    fn fish_function_files() {
+        let mut table = AliasTable::new();
+        table.parse_fish_function(
+            "gst",
+            "function gst --wraps='git status' --description 'alias gst=git status'\n  git status $argv\nend\n",
+        );
+        table.parse_fish_function(
+            "fco",
+            "function fco\n  git branch | fzf | xargs git checkout\nend\n",
+        );
+
+        assert_eq!(resolved(&table, "gst").command, "git");
+        assert_eq!(resolved(&table, "fco").command, "fco");
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn steps_follow_the_expansion() {
+        let mut table = AliasTable::new();
+        table.parse_rc(
+            "alias gp='git push'\nalias g=git\nalias ship='cargo test && gp'\n",
+            false,
+        );
+        let steps = |line: &str| HistoryEntry::from_command_line(line, None, 1).steps;
+
+        assert_eq!(resolved(&table, "gp origin").steps, steps("git push"));
+        assert_eq!(resolved(&table, "g commit -m x").steps, steps("git commit"));
+        assert_eq!(
+            resolved(&table, "git add . && gp").steps,
+            steps("git add && git push")
+        );
+        assert_eq!(
+            resolved(&table, "ship").steps,
+            steps("cargo test && git push")
+        );
+
+        // Cached entries have no line: the expansion alone decides
+        let mut cached = HistoryEntry::from_command_line("g commit", None, 1);
+        table.resolve(&mut cached);
+        assert_eq!(cached.steps, steps("git"));
+    }
+
+    #[test]
+    fn unaliased_entries_are_untouched() {
+        let mut table = AliasTable::new();
+        table.parse_rc("alias gst='git status'\n", false);
+
+        let entry = resolved(&table, "cargo test | tee log");
+        assert_eq!(entry.commands, ["cargo", "tee"]);
+        assert!(entry.alias.is_none());
+    }
+
+    #[test]
+    fn source_lines_are_followed() {
+        let dir = std::env::temp_dir().join("absurdtty_test_aliases_source");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let aliases = dir.join("aliases.zsh");
+        std::fs::write(&aliases, "alias gd='git diff'\n").unwrap();
+        let rc = dir.join(".zshrc");
+        std::fs::write(&rc, format!("source {}\n", aliases.display())).unwrap();
+
+        let table = AliasTable::load(&[rc]).unwrap();
+        assert_eq!(resolved(&table, "gd").command, "git");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,598 @@
+//! asciinema recording parser.
+//!
+//! Reads asciicast v2 files (`asciinema rec session.cast`): a JSON
+//! header line followed by one JSON array per terminal event:
+//!
+//! ```text
+//! {"version": 2, "width": 80, "height": 24, "timestamp": 1702400000}
+//! [0.84, "o", "~/src $ "]
+//! [1.52, "i", "g"]
+//! [1.52, "o", "g"]
+//! [1.71, "i", "s"]
+//! ...
+//! [2.40, "i", "\r"]
+//! ```
+//!
+//! Where:
+//! - `timestamp` in the header is when recording started (Unix epoch seconds)
+//! - each event carries seconds since the start, a code (`o` output,
+//!   `i` input, others ignored) and the data written
+//!
+//! Commands are reconstructed from keystrokes when the recording has
+//! input events (`asciinema rec --stdin`). When completion, history
+//! recall or cursor movement make the keystrokes unreliable, the line
+//! echoed to the terminal is used instead, minus the prompt. Recordings
+//! without input events fall back to echoed lines that follow a prompt.
+//!
+//! Each entry keeps the pauses between its keystrokes, and is timestamped
+//! at the moment Enter was pressed.
+
+use super::{entry::HistoryEntry, HistoryParser};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::path::Path;
+use time::{Duration, OffsetDateTime};
+
+/// Strings that commonly end a shell prompt.
+const PROMPT_MARKERS: &[&str] = &["$ ", "# ", "% ", "> ", "❯ ", "➜ ", "λ "];
+
+/// Parser for asciicast v2 recordings.
+#[derive(Debug, Default)]
+pub struct AsciicastParser {
+    /// Whether to preserve full command lines (for internal analysis)
+    preserve_full_line: bool,
+}
+
+/// The recording's header line.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+    #[serde(default)]
+    timestamp: Option<i64>,
+}
+
+/// A reconstructed command, with times in seconds since recording start.
+struct Command {
+    text: String,
+    /// When Enter was pressed (or the echoed line completed)
+    time: f64,
+    /// When each keystroke was typed, ending with Enter
+    keystrokes: Vec<f64>,
+    line_number: usize,
+}
+
+/// A command submitted with Enter whose echoed line hasn't completed yet.
+struct Submitted {
+    typed: String,
+    /// Whether `typed` is exactly what was run
+    trusted: bool,
+    keystrokes: Vec<f64>,
+    line_number: usize,
+}
+
+/// Replays a recording's input and output events.
+#[derive(Default)]
+struct Replay {
+    saw_input: bool,
+    /// Text typed on the current line
+    typed: String,
+    /// Whether the current line was edited in ways we can't follow
+    edited: bool,
+    keystrokes: Vec<f64>,
+    submitted: VecDeque<Submitted>,
+    /// The terminal line currently being written, and the cursor on it
+    screen: Vec<char>,
+    cursor: usize,
+    /// Completed terminal lines that follow a prompt (for output-only recordings)
+    prompted: Vec<Command>,
+    commands: Vec<Command>,
+}
+
+impl AsciicastParser {
+    /// Create a new asciicast parser.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure whether to preserve full command lines.
+    pub fn preserve_full_lines(mut self, preserve: bool) -> Self {
+        self.preserve_full_line = preserve;
+        self
+    }
+
+    /// Parse a whole recording.
+    fn parse_content(&self, content: &str) -> Result<Vec<HistoryEntry>> {
+        let mut lines = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+
+        let header: Header = match lines.next() {
+            Some((_, line)) => serde_json::from_str(line).context("Invalid asciicast header")?,
+            None => return Ok(Vec::new()),
+        };
+
+        if header.version != 2 {
+            anyhow::bail!(
+                "Unsupported asciicast version {} (only v2 is supported)",
+                header.version
+            );
+        }
+
+        let start = header
+            .timestamp
+            .and_then(|ts| OffsetDateTime::from_unix_timestamp(ts).ok());
+        let session = header.timestamp.map(|ts| format!("asciicast-{}", ts));
+
+        let mut replay = Replay::default();
+        for (index, line) in lines {
+            // A recording cut short can end in a partial event
+            let Ok((time, code, data)) =
+                serde_json::from_str::<(f64, String, serde_json::Value)>(line)
+            else {
+                continue;
+            };
+            let Some(data) = data.as_str() else {
+                continue;
+            };
+            // A damaged recording can hold times no timestamp can represent
+            if !Self::in_range(time, start) {
+                continue;
+            }
+
+            match code.as_str() {
+                "i" => replay.input(time, data, index + 1),
+                "o" => replay.output(time, data),
+                _ => {}
+            }
+        }
+
+        Ok(replay
+            .finish()
+            .into_iter()
+            .filter_map(|command| self.make_entry(command, start, session.clone()))
+            .collect())
+    }
+
+    /// Whether an event time is a usable offset from the recording start.
+    fn in_range(time: f64, start: Option<OffsetDateTime>) -> bool {
+        if time < 0.0 {
+            return false;
+        }
+        match Duration::checked_seconds_f64(time) {
+            Some(offset) => start.is_none_or(|start| start.checked_add(offset).is_some()),
+            None => false,
+        }
+    }
+
+    /// Build an entry from a reconstructed command.
+    fn make_entry(
+        &self,
+        command: Command,
+        start: Option<OffsetDateTime>,
+        session: Option<String>,
+    ) -> Option<HistoryEntry> {
+        if command.text.trim().is_empty() {
+            return None;
+        }
+
+        let timestamp =
+            start.and_then(|start| start.checked_add(Duration::seconds_f64(command.time)));
+        let mut entry =
+            HistoryEntry::from_command_line(&command.text, timestamp, command.line_number);
+        entry.session = session;
+        entry.keystroke_gaps = command
+            .keystrokes
+            .windows(2)
+            .map(|pair| Duration::seconds_f64((pair[1] - pair[0]).max(0.0)))
+            .collect();
+
+        if self.preserve_full_line {
+            entry = entry.with_full_line(command.text);
+        }
+
+        Some(entry)
+    }
+}
+
+impl HistoryParser for AsciicastParser {
+    fn parse_file(&self, path: &Path) -> Result<Vec<HistoryEntry>> {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("Failed to read recording: {:?}", path))?;
+        let content = String::from_utf8_lossy(&bytes);
+        self.parse_content(&content)
+            .with_context(|| format!("Failed to parse recording: {:?}", path))
+    }
+
+    fn parse_str(&self, content: &str) -> Result<Vec<HistoryEntry>> {
+        self.parse_content(content)
+    }
+}
+
+impl Replay {
+    /// Apply keystrokes.
+    fn input(&mut self, time: f64, data: &str, line_number: usize) {
+        self.saw_input = true;
+        let mut chars = data.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' | '\n' => {
+                    self.keystrokes.push(time);
+                    self.submit(line_number);
+                    continue;
+                }
+                // Backspace
+                '\x7f' | '\x08' => {
+                    self.typed.pop();
+                }
+                // Ctrl-U: kill line
+                '\x15' => self.typed.clear(),
+                // Ctrl-W: kill word
+                '\x17' => {
+                    let kept = self.typed.trim_end().rfind(' ').map_or(0, |i| i + 1);
+                    self.typed.truncate(kept);
+                }
+                // Ctrl-C: abandon line
+                '\x03' => {
+                    self.typed.clear();
+                    self.edited = false;
+                    self.keystrokes.clear();
+                    continue;
+                }
+                '\x1b' => match skip_escape(&mut chars).as_deref() {
+                    // Bracketed paste markers wrap plain text
+                    Some("200~") | Some("201~") => {}
+                    // Arrows, Alt-keys: moving through the line or history
+                    _ => self.edited = true,
+                },
+                c if c.is_control() => self.edited = true,
+                c => self.typed.push(c),
+            }
+            self.keystrokes.push(time);
+        }
+    }
+
+    /// Enter was pressed: queue the line until its echo completes.
+    fn submit(&mut self, line_number: usize) {
+        let keystrokes = std::mem::take(&mut self.keystrokes);
+
+        if self.typed.trim().is_empty() && !self.edited {
+            return;
+        }
+
+        self.submitted.push_back(Submitted {
+            typed: std::mem::take(&mut self.typed),
+            trusted: !self.edited,
+            keystrokes,
+            line_number,
+        });
+        self.edited = false;
+    }
+
+    /// Apply terminal output to the current screen line.
+    fn output(&mut self, time: f64, data: &str) {
+        let mut chars = data.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\n' => self.complete_line(time),
+                '\r' => self.cursor = 0,
+                '\x08' => self.cursor = self.cursor.saturating_sub(1),
+                '\x1b' => {
+                    if let Some(sequence) = skip_escape(&mut chars) {
+                        self.apply_csi(&sequence);
+                    }
+                }
+                c if c.is_control() => {}
+                c => {
+                    if self.cursor < self.screen.len() {
+                        self.screen[self.cursor] = c;
+                    } else {
+                        self.screen.push(c);
+                    }
+                    self.cursor += 1;
+                }
+            }
+        }
+    }
+
+    /// Apply the cursor and erase sequences that shells use to redraw a line.
+    fn apply_csi(&mut self, sequence: &str) {
+        let Some(last) = sequence.chars().last() else {
+            return;
+        };
+        let param = &sequence[..sequence.len() - last.len_utf8()];
+        let n = param.parse::<usize>().unwrap_or(1).max(1);
+
+        match last {
+            'C' => self.cursor = (self.cursor + n).min(self.screen.len()),
+            'D' => self.cursor = self.cursor.saturating_sub(n),
+            'G' => self.cursor = (n - 1).min(self.screen.len()),
+            'K' => match param {
+                "1" => {
+                    let end = self.cursor.min(self.screen.len());
+                    self.screen[..end].fill(' ');
+                }
+                "2" => self.screen.clear(),
+                _ => self.screen.truncate(self.cursor),
+            },
+            'P' => {
+                let end = (self.cursor + n).min(self.screen.len());
+                self.screen.drain(self.cursor.min(end)..end);
+            }
+            '@' => {
+                let at = self.cursor.min(self.screen.len());
+                self.screen.splice(at..at, std::iter::repeat_n(' ', n));
+            }
+            _ => {}
+        }
+    }
+
+    /// A terminal line was finished.
+    fn complete_line(&mut self, time: f64) {
+        let line: String = self.screen.drain(..).collect();
+        self.cursor = 0;
+
+        if let Some(submitted) = self.submitted.pop_front() {
+            let text = if submitted.trusted {
+                submitted.typed
+            } else {
+                strip_prompt(&line).unwrap_or(&submitted.typed).to_string()
+            };
+            self.commands.push(Command {
+                text,
+                time: submitted.keystrokes.last().copied().unwrap_or(time),
+                keystrokes: submitted.keystrokes,
+                line_number: submitted.line_number,
+            });
+        } else if !self.saw_input {
+            if let Some(text) = strip_prompt(&line) {
+                self.prompted.push(Command {
+                    text: text.to_string(),
+                    time,
+                    keystrokes: Vec::new(),
+                    line_number: 0,
+                });
+            }
+        }
+    }
+
+    /// Commands in the order they were run.
+    fn finish(mut self) -> Vec<Command> {
+        if !self.saw_input {
+            return self.prompted;
+        }
+
+        // Lines submitted as the recording ended never got their echo
+        for submitted in self.submitted.drain(..) {
+            self.commands.push(Command {
+                text: submitted.typed,
+                time: submitted.keystrokes.last().copied().unwrap_or_default(),
+                keystrokes: submitted.keystrokes,
+                line_number: submitted.line_number,
+            });
+        }
+
+        self.commands
+    }
+}
+
+/// Skip an escape sequence after `ESC`, returning a CSI sequence's
+/// parameters and final character (e.g. `2K`).
+fn skip_escape(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<String> {
+    match chars.next()? {
+        '[' => {
+            let mut sequence = String::new();
+            for c in chars.by_ref() {
+                sequence.push(c);
+                if ('\x40'..='\x7e').contains(&c) {
+                    break;
+                }
+            }
+            Some(sequence)
+        }
+        // OSC (window titles, cwd reports), ended by BEL or ESC \
+        ']' => {
+            while let Some(c) = chars.next() {
+                if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                    break;
+                }
+            }
+            None
+        }
+        // SS3 (application cursor keys): one more character
+        'O' => {
+            chars.next();
+            None
+        }
+        _ => None,
+    }
+}
+
+/// The text after the first prompt marker on a line, if any.
+fn strip_prompt(line: &str) -> Option<&str> {
+    let (at, marker) = PROMPT_MARKERS
+        .iter()
+        .filter_map(|marker| line.find(marker).map(|at| (at, marker)))
+        .min_by_key(|(at, _)| *at)?;
+
+    let command = line[at + marker.len()..].trim();
+    (!command.is_empty()).then_some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = r#"{"version": 2, "width": 80, "height": 24, "timestamp": 1702400000}"#;
+
+    fn cast(events: &[(f64, &str, &str)]) -> String {
+        let mut content = format!("{}\n", HEADER);
+        for (time, code, data) in events {
+            content.push_str(&serde_json::to_string(&(time, code, data)).unwrap());
+            content.push('\n');
+        }
+        content
+    }
+
+    /// Type `text` one key at a time, echoing each key.
+    fn typed(start: f64, text: &str) -> Vec<(f64, &'static str, String)> {
+        text.chars()
+            .enumerate()
+            .flat_map(|(i, c)| {
+                let time = start + i as f64 * 0.2;
+                [
+                    (time, "i", c.to_string()),
+                    (time + 0.01, "o", c.to_string()),
+                ]
+            })
+            .collect()
+    }
+
+    fn parse(events: Vec<(f64, &str, String)>) -> Vec<HistoryEntry> {
+        let events: Vec<_> = events
+            .iter()
+            .map(|(t, c, d)| (*t, *c, d.as_str()))
+            .collect();
+        AsciicastParser::new()
+            .preserve_full_lines(true)
+            .parse_content(&cast(&events))
+            .unwrap()
+    }
+
+    #[test]
+    fn keystrokes_become_commands() {
+        let mut events = vec![(0.5, "o", "~/src $ ".to_string())];
+        events.extend(typed(1.0, "git status"));
+        events.push((4.0, "i", "\r".to_string()));
+        events.push((4.01, "o", "\r\nOn branch main\r\n~/src $ ".to_string()));
+        events.extend(typed(5.0, "ls"));
+        events.push((5.5, "i", "\r".to_string()));
+        events.push((5.51, "o", "\r\nCargo.toml\r\n".to_string()));
+
+        let entries = parse(events);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].full_line, "git status");
+        assert_eq!(entries[1].command, "ls");
+        assert_eq!(entries[0].timestamp.unwrap().unix_timestamp(), 1702400004);
+        assert_eq!(entries[0].session.as_deref(), Some("asciicast-1702400000"));
+
+        // Ten keys and Enter: ten gaps, the last one a pause before Enter
+        let gaps: Vec<f64> = entries[0]
+            .keystroke_gaps
+            .iter()
+            .map(|gap| gap.as_seconds_f64())
+            .collect();
+        assert_eq!(gaps.len(), 10);
+        assert!((gaps[0] - 0.2).abs() < 1e-6);
+        assert!((gaps[9] - 1.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn out_of_range_times_are_skipped() {
+        let mut events = typed(1.0, "ls");
+        events.push((1e300, "i", "x".to_string()));
+        events.push((1e12, "i", "y".to_string()));
+        events.push((2.0, "i", "\r".to_string()));
+        events.push((2.01, "o", "\r\n".to_string()));
+
+        let entries = parse(events);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].full_line, "ls");
+        assert_eq!(entries[0].timestamp.unwrap().unix_timestamp(), 1702400002);
+    }
+
+    #[test]
+    fn line_editing_keys_are_applied() {
+        let mut events = typed(1.0, "gti");
+        events.push((2.0, "i", "\x7f\x7f".to_string()));
+        events.extend(typed(2.5, "it log"));
+        events.push((4.0, "i", "\x17".to_string()));
+        events.extend(typed(4.5, "diff"));
+        events.push((6.0, "i", "\r".to_string()));
+        events.push((6.01, "o", "\r\n".to_string()));
+
+        let entries = parse(events);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].full_line, "git diff");
+    }
+
+    #[test]
+    fn completion_falls_back_to_echoed_line() {
+        let mut events = vec![(0.5, "o", "user@box:~$ ".to_string())];
+        events.extend(typed(1.0, "carg"));
+        events.push((2.0, "i", "\t".to_string()));
+        events.push((2.01, "o", "o ".to_string()));
+        events.extend(typed(2.5, "test"));
+        events.push((4.0, "i", "\r".to_string()));
+        events.push((4.01, "o", "\r\n".to_string()));
+
+        let entries = parse(events);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].full_line, "cargo test");
+    }
+
+    #[test]
+    fn history_recall_uses_redrawn_line() {
+        let events = vec![
+            (0.5, "o", "$ ".to_string()),
+            (1.0, "i", "\x1b[A".to_string()),
+            (1.01, "o", "make check".to_string()),
+            (1.5, "i", "\x1b[A".to_string()),
+            (1.51, "o", "\r\x1b[K$ cargo fmt".to_string()),
+            (2.0, "i", "\r".to_string()),
+            (2.01, "o", "\r\n".to_string()),
+        ];
+
+        let entries = parse(events);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].full_line, "cargo fmt");
+    }
+
+    #[test]
+    fn interrupted_lines_are_dropped() {
+        let mut events = typed(1.0, "rm -rf build");
+        events.push((3.0, "i", "\x03".to_string()));
+        events.push((3.01, "o", "^C\r\n$ ".to_string()));
+        events.push((4.0, "i", "\r".to_string()));
+        events.push((4.01, "o", "\r\n".to_string()));
+
+        assert!(parse(events).is_empty());
+    }
+
+    #[test]
+    fn output_only_recordings_use_prompts() {
+        let events = vec![
+            (0.5, "o", "\x1b]0;title\x07~/src $ ".to_string()),
+            (1.0, "o", "cargo build\r\n".to_string()),
+            (9.0, "o", "   Compiling tty-mood\r\n~/src $ ".to_string()),
+            (10.0, "o", "exit\r\n".to_string()),
+        ];
+
+        let entries = parse(events);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].full_line, "cargo build");
+        assert_eq!(entries[1].command, "exit");
+        assert_eq!(entries[1].timestamp.unwrap().unix_timestamp(), 1702400010);
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let parser = AsciicastParser::new();
+        assert!(parser.parse_content("{\"version\": 1}\n").is_err());
+        assert!(parser.parse_content("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn truncated_events_are_skipped() {
+        let content = format!(
+            "{}\n[1.0, \"o\", \"$ ls\\r\\n\"]\n[2.0, \"o\", \"$ pw",
+            HEADER
+        );
+        let entries = AsciicastParser::new().parse_content(&content).unwrap();
+        assert_eq!(entries.len(), 1);
+    }
+}
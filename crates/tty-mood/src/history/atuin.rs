@@ -0,0 +1,217 @@
+//! Atuin history database reader.
+//!
+//! Atuin keeps shell history in a local SQLite database
+//! (`~/.local/share/atuin/history.db`) with one row per command:
+//!
+//! ```text
+//! history(id, timestamp, duration, exit, command, cwd, session, hostname, deleted_at)
+//! ```
+//!
+//! Where:
+//! - `timestamp` is Unix epoch nanoseconds
+//! - `duration` is nanoseconds (-1 while the command is still running)
+//! - `exit` is the exit status (-1 if unknown)
+//! - `deleted_at` is set for entries removed with `atuin history delete`
+//!
+//! The database is opened read-only. We never modify it.
+
+use super::{entry::HistoryEntry, HistoryParser};
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OpenFlags};
+use std::path::Path;
+use time::{Duration, OffsetDateTime};
+
+/// Reader for Atuin's `history.db`.
+#[derive(Debug, Default)]
+pub struct AtuinHistoryParser {
+    /// Whether to preserve full command lines (for internal analysis)
+    preserve_full_line: bool,
+}
+
+/// A raw row from the `history` table.
+struct AtuinRow {
+    timestamp_ns: i64,
+    duration_ns: i64,
+    exit: i64,
+    command: String,
+    cwd: String,
+    session: String,
+    hostname: String,
+}
+
+impl AtuinHistoryParser {
+    /// Create a new Atuin history reader.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure whether to preserve full command lines.
+    pub fn preserve_full_lines(mut self, preserve: bool) -> Self {
+        self.preserve_full_line = preserve;
+        self
+    }
+
+    /// Read all live rows from an open connection, oldest first.
+    fn read_rows(&self, conn: &Connection) -> Result<Vec<HistoryEntry>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT timestamp, duration, exit, command, cwd, session, hostname
+                 FROM history
+                 WHERE deleted_at IS NULL
+                 ORDER BY timestamp",
+            )
+            .context("Failed to query Atuin history table")?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(AtuinRow {
+                timestamp_ns: row.get(0)?,
+                duration_ns: row.get(1)?,
+                exit: row.get(2)?,
+                command: row.get(3)?,
+                cwd: row.get(4)?,
+                session: row.get(5)?,
+                hostname: row.get(6)?,
+            })
+        })?;
+
+        let mut entries = Vec::new();
+        for (index, row) in rows.enumerate() {
+            if let Some(entry) = self.row_to_entry(row?, index + 1) {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Map an Atuin row onto a history entry.
+    fn row_to_entry(&self, row: AtuinRow, line_number: usize) -> Option<HistoryEntry> {
+        if row.command.trim().is_empty() {
+            return None;
+        }
+
+        let timestamp =
+            OffsetDateTime::from_unix_timestamp_nanos(i128::from(row.timestamp_ns)).ok();
+
+        let mut entry = HistoryEntry::from_command_line(&row.command, timestamp, line_number);
+
+        entry.duration = (row.duration_ns >= 0).then(|| Duration::nanoseconds(row.duration_ns));
+        entry.exit_status = (row.exit >= 0).then_some(row.exit as i32);
+        entry.cwd = non_empty(row.cwd);
+        entry.session = non_empty(row.session);
+        entry.hostname = non_empty(row.hostname);
+
+        if self.preserve_full_line {
+            entry = entry.with_full_line(row.command);
+        }
+
+        Some(entry)
+    }
+}
+
+impl HistoryParser for AtuinHistoryParser {
+    fn parse_file(&self, path: &Path) -> Result<Vec<HistoryEntry>> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .with_context(|| format!("Failed to open Atuin database: {:?}", path))?;
+
+        self.read_rows(&conn)
+    }
+
+    fn parse_str(&self, _content: &str) -> Result<Vec<HistoryEntry>> {
+        anyhow::bail!("Atuin history is a SQLite database and cannot be parsed from text")
+    }
+}
+
+/// Treat empty strings as missing values.
+fn non_empty(value: String) -> Option<String> {
+    (!value.is_empty()).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = "CREATE TABLE history (
+        id TEXT PRIMARY KEY,
+        timestamp INTEGER NOT NULL,
+        duration INTEGER NOT NULL,
+        exit INTEGER NOT NULL,
+        command TEXT NOT NULL,
+        cwd TEXT NOT NULL,
+        session TEXT NOT NULL,
+        hostname TEXT NOT NULL,
+        deleted_at INTEGER
+    )";
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.execute_batch(
+            "INSERT INTO history VALUES
+                ('b', 1702400100000000000, 2500000000, 101, 'cargo test', '/src', 's1', 'box:me', NULL),
+                ('a', 1702400000000000000, 40000000, 0, 'git status', '/src', 's1', 'box:me', NULL),
+                ('c', 1702400200000000000, -1, -1, 'sleep 100', '', 's2', 'box:me', NULL),
+                ('d', 1702400300000000000, 1000, 0, 'pass show bank', '/', 's2', 'box:me', 1702400400000000000)",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn rows_map_to_entries_in_order() {
+        let parser = AtuinHistoryParser::new();
+        let entries = parser.read_rows(&test_db()).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].command, "git");
+        assert_eq!(entries[1].command, "cargo");
+        assert_eq!(entries[0].timestamp.unwrap().unix_timestamp(), 1702400000);
+    }
+
+    #[test]
+    fn exit_and_duration_preserved() {
+        let parser = AtuinHistoryParser::new();
+        let entries = parser.read_rows(&test_db()).unwrap();
+
+        assert_eq!(entries[0].exit_status, Some(0));
+        assert_eq!(entries[0].duration, Some(Duration::milliseconds(40)));
+        assert_eq!(entries[1].exit_status, Some(101));
+        assert!(entries[1].failed());
+        assert_eq!(entries[1].cwd.as_deref(), Some("/src"));
+        assert_eq!(entries[1].session.as_deref(), Some("s1"));
+        assert_eq!(entries[1].hostname.as_deref(), Some("box:me"));
+    }
+
+    #[test]
+    fn running_commands_have_unknown_exit_and_duration() {
+        let parser = AtuinHistoryParser::new();
+        let entries = parser.read_rows(&test_db()).unwrap();
+
+        assert_eq!(entries[2].command, "sleep");
+        assert!(entries[2].exit_status.is_none());
+        assert!(entries[2].duration.is_none());
+        assert!(entries[2].cwd.is_none());
+    }
+
+    #[test]
+    fn parse_file_opens_read_only() {
+        let path = std::env::temp_dir().join("absurdtty_test_atuin.db");
+        let _ = std::fs::remove_file(&path);
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(SCHEMA).unwrap();
+            conn.execute_batch(
+                "INSERT INTO history VALUES ('a', 1702400000000000000, 0, 0, 'ls', '/', 's', 'h', NULL)",
+            )
+            .unwrap();
+        }
+
+        let entries = AtuinHistoryParser::new().parse_file(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,204 @@
+//! Bash history parser.
+//!
+//! Parses the plain `.bash_history` format. When `HISTTIMEFORMAT` is set,
+//! bash writes a comment line with the Unix epoch before each command:
+//! `#TIMESTAMP`
+//!
+//! Where:
+//! - TIMESTAMP is Unix epoch seconds
+//! - every line until the next timestamp belongs to the same command
+//!   (bash stores multi-line commands this way when `lithist` is set)
+//!
+//! Without `HISTTIMEFORMAT`, every line is a separate command without
+//! a timestamp.
+//!
+//! Example:
+//! ```text
+//! #1702400000
+//! git status
+//! #1702400100
+//! cd ~/projects
+//! ```
+
+use super::{entry::HistoryEntry, HistoryParser};
+use anyhow::{Context, Result};
+use std::path::Path;
+use time::OffsetDateTime;
+
+/// Parser for bash history, with or without `HISTTIMEFORMAT` timestamps.
+#[derive(Debug, Default)]
+pub struct BashHistoryParser {
+    /// Whether to preserve full command lines (for internal analysis)
+    preserve_full_line: bool,
+}
+
+impl BashHistoryParser {
+    /// Create a new bash history parser.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure whether to preserve full command lines.
+    pub fn preserve_full_lines(mut self, preserve: bool) -> Self {
+        self.preserve_full_line = preserve;
+        self
+    }
+
+    /// Build an entry from a (possibly multi-line) command.
+    fn make_entry(
+        &self,
+        full_command: &str,
+        timestamp: Option<OffsetDateTime>,
+        line_number: usize,
+    ) -> Option<HistoryEntry> {
+        if full_command.trim().is_empty() {
+            return None;
+        }
+
+        let mut entry = HistoryEntry::from_command_line(full_command, timestamp, line_number);
+
+        if self.preserve_full_line {
+            entry = entry.with_full_line(full_command.to_string());
+        }
+
+        Some(entry)
+    }
+
+    /// Parse content, grouping lines between timestamp markers.
+    fn parse_content(&self, content: &str) -> Vec<HistoryEntry> {
+        let mut entries = Vec::new();
+        let mut current_command = String::new();
+        let mut current_timestamp: Option<OffsetDateTime> = None;
+        let mut start_line_number = 1;
+        let mut seen_timestamp = false;
+
+        for (index, line) in content.lines().enumerate() {
+            let line_number = index + 1;
+
+            if let Some(timestamp) = parse_timestamp_line(line) {
+                // A new marker completes the previous command
+                if let Some(entry) =
+                    self.make_entry(&current_command, current_timestamp, start_line_number)
+                {
+                    entries.push(entry);
+                }
+                current_command.clear();
+                current_timestamp = Some(timestamp);
+                start_line_number = line_number + 1;
+                seen_timestamp = true;
+                continue;
+            }
+
+            if !seen_timestamp {
+                // Plain format: one command per line, comments are skipped
+                if !line.starts_with('#') {
+                    if let Some(entry) = self.make_entry(line, None, line_number) {
+                        entries.push(entry);
+                    }
+                }
+                continue;
+            }
+
+            // Timestamped format: lines accumulate until the next marker
+            if !current_command.is_empty() {
+                current_command.push('\n');
+            }
+            current_command.push_str(line);
+        }
+
+        if let Some(entry) = self.make_entry(&current_command, current_timestamp, start_line_number)
+        {
+            entries.push(entry);
+        }
+
+        entries
+    }
+}
+
+impl HistoryParser for BashHistoryParser {
+    fn parse_file(&self, path: &Path) -> Result<Vec<HistoryEntry>> {
+        // Bash history has no guaranteed encoding, so decode lossily
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read history file: {:?}", path))?;
+        let content = String::from_utf8_lossy(&bytes);
+        Ok(self.parse_content(&content))
+    }
+
+    fn parse_str(&self, content: &str) -> Result<Vec<HistoryEntry>> {
+        Ok(self.parse_content(content))
+    }
+}
+
+/// Parse a `#TIMESTAMP` marker line written by bash with `HISTTIMEFORMAT`.
+///
+/// Returns None for ordinary comments and commands.
+fn parse_timestamp_line(line: &str) -> Option<OffsetDateTime> {
+    let digits = line.strip_prefix('#')?.trim_end();
+
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    digits
+        .parse::<i64>()
+        .ok()
+        .and_then(|ts| OffsetDateTime::from_unix_timestamp(ts).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_timestamped_format() {
+        let parser = BashHistoryParser::new();
+        let entries = parser.parse_content("#1702400000\ngit status\n#1702400100\ncd ~/projects\n");
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].command, "git");
+        assert_eq!(entries[1].command, "cd");
+        assert_eq!(entries[0].timestamp.unwrap().unix_timestamp(), 1702400000);
+        assert_eq!(entries[1].timestamp.unwrap().unix_timestamp(), 1702400100);
+        assert_eq!(entries[1].line_number, 4);
+    }
+
+    #[test]
+    fn parse_plain_format() {
+        let parser = BashHistoryParser::new();
+        let entries = parser.parse_content("ls -la\n# just a comment\nsudo pacman -Syu\n");
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].command, "ls");
+        assert_eq!(entries[1].command, "pacman");
+        assert!(entries[0].timestamp.is_none());
+    }
+
+    #[test]
+    fn multiline_command_between_markers() {
+        let parser = BashHistoryParser::new().preserve_full_lines(true);
+        let content = "#1702400000\nfor f in *.rs; do\n  wc -l \"$f\"\ndone\n#1702400100\nls\n";
+        let entries = parser.parse_content(content);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].command, "wc");
+        assert_eq!(entries[0].full_line.lines().count(), 3);
+        assert_eq!(entries[1].command, "ls");
+    }
+
+    #[test]
+    fn comment_is_not_a_timestamp() {
+        assert!(parse_timestamp_line("#1702400000").is_some());
+        assert!(parse_timestamp_line("# 1702400000").is_none());
+        assert!(parse_timestamp_line("#todo").is_none());
+        assert!(parse_timestamp_line("#").is_none());
+    }
+
+    #[test]
+    fn empty_marker_groups_are_skipped() {
+        let parser = BashHistoryParser::new();
+        let entries = parser.parse_content("#1702400000\n#1702400050\nmake\n");
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].timestamp.unwrap().unix_timestamp(), 1702400050);
+    }
+}
@@ -0,0 +1,462 @@
+//! Incremental history reading with a persisted cursor.
+//!
+//! Large histories make a full parse on every `generate` too slow for
+//! a prompt hook. For append-only formats (zsh, bash, fish) we remember
+//! how far we got:
+//!
+//! - the file's inode, to notice rotation
+//! - the byte offset after the last complete line
+//! - a hash of that last line, to notice rewrites and truncation
+//!
+//! Next time only the bytes after the offset are parsed. Entries read
+//! earlier come from a cache of per-entry aggregates stored next to the
+//! cursor. The cache never contains command lines, only the command
+//! names, fingerprints of the project and of the command's steps, and
+//! the metadata the signals need. Line shapes are left out as well, so
+//! cached entries carry none. If anything about the file doesn't match,
+//! we fall back to a full re-read.
+//!
+//! The cursor saves parsing, not analysis: signals are still computed
+//! from all entries on every run, since the analyzed range and the
+//! trend window move with the clock. Per-signal running totals would
+//! have to be rebuilt each time anyway.
+//!
+//! Cursors live under `~/.local/share/absurdtty/cursors/`.
+
+use super::{detect::Detection, fingerprint, HistoryEntry, HistoryParser};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use time::{Duration, OffsetDateTime};
+
+/// Bump when the cache layout changes; old caches are then ignored.
+const CACHE_VERSION: u32 = 1;
+
+/// Where we stopped reading a history file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Cursor {
+    /// Inode of the file when it was read
+    inode: u64,
+    /// Byte offset after the last complete line
+    offset: u64,
+    /// Byte offset where the last complete line starts
+    last_line_start: u64,
+    /// Hash of the last complete line
+    last_line_hash: u64,
+    /// Number of lines before `offset`
+    lines: usize,
+}
+
+/// Cursor plus cached entries for one history file.
+#[derive(Debug, Serialize, Deserialize)]
+struct CursorCache {
+    version: u32,
+    path: String,
+    format: String,
+    /// Parser settings (ignore rules) the entries were produced with
+    parser_key: u64,
+    cursor: Cursor,
+    entries: Vec<CachedEntry>,
+}
+
+/// Per-entry aggregate kept between runs.
+///
+/// Deliberately lossy: no arguments, paths or full command lines. The
+/// working directory, host name and fish's referenced paths are not
+/// kept either; [`CachedEntry::strip`] drops them from freshly parsed
+/// entries too, so both kinds look the same to the signals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedEntry {
+    command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    commands: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timestamp_ns: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration_ms: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exit_status: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    session: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    steps: Vec<u64>,
+    line_hash: u64,
+    line_number: usize,
+}
+
+/// Result of an incremental read.
+#[derive(Debug)]
+pub struct IncrementalRead {
+    /// All entries: cached ones first, then newly parsed ones
+    pub entries: Vec<HistoryEntry>,
+    /// How many entries were parsed in this run
+    pub parsed: usize,
+    /// Whether the cursor was unusable and the whole file was read
+    pub full_read: bool,
+}
+
+/// Reads history files incrementally using cursors in a cache directory.
+pub struct IncrementalReader {
+    cache_dir: PathBuf,
+}
+
+impl IncrementalReader {
+    /// Create a reader using the default cursor directory.
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_dir(
+            absurd_core::Paths::data_dir()?.join("cursors"),
+        ))
+    }
+
+    /// Create a reader storing cursors in a specific directory.
+    pub fn with_dir(cache_dir: PathBuf) -> Self {
+        Self { cache_dir }
+    }
+
+    /// Read a history file, parsing only what was appended since last time.
+    pub fn read(
+        &self,
+        detection: &Detection,
+        parser: &dyn HistoryParser,
+    ) -> Result<IncrementalRead> {
+        // Key the cursor on the absolute path so the working directory doesn't matter
+        let path = &detection
+            .path
+            .canonicalize()
+            .unwrap_or_else(|_| detection.path.clone());
+        let format = detection.format.name();
+
+        let mut file =
+            File::open(path).with_context(|| format!("Failed to open history file: {:?}", path))?;
+        let metadata = file.metadata()?;
+        let inode = inode(&metadata);
+
+        let cache_path = self.cache_path(path, format);
+        let previous = load_cache(&cache_path)
+            .filter(|c| c.path == path.to_string_lossy() && c.format == format)
+            .filter(|c| c.parser_key == parser.cache_key())
+            .filter(|c| c.cursor.inode == inode && c.cursor.offset <= metadata.len())
+            .filter(|c| last_line_matches(&mut file, &c.cursor));
+
+        let full_read = previous.is_none();
+        let (previous_cursor, mut cached) = match previous {
+            Some(cache) => (Some(cache.cursor), cache.entries),
+            None => (None, Vec::new()),
+        };
+        let start = previous_cursor.as_ref().map_or(0, |c| c.offset);
+        let lines_before = previous_cursor.as_ref().map_or(0, |c| c.lines);
+
+        // Read the unread tail, stopping after the last complete line
+        let mut tail = Vec::new();
+        file.seek(SeekFrom::Start(start))?;
+        file.read_to_end(&mut tail)
+            .with_context(|| format!("Failed to read history file: {:?}", path))?;
+        let complete = tail.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        tail.truncate(complete);
+
+        let mut parsed = parser.parse_bytes(&tail)?;
+        for entry in &mut parsed {
+            entry.line_number += lines_before;
+            CachedEntry::strip(entry);
+        }
+
+        // Advance the cursor past the last complete line
+        let cursor = match tail.len() {
+            0 => previous_cursor.unwrap_or(Cursor {
+                inode,
+                offset: 0,
+                last_line_start: 0,
+                last_line_hash: fingerprint(b""),
+                lines: 0,
+            }),
+            len => {
+                let last_start = tail[..len - 1]
+                    .iter()
+                    .rposition(|&b| b == b'\n')
+                    .map_or(0, |i| i + 1);
+                Cursor {
+                    inode,
+                    offset: start + len as u64,
+                    last_line_start: start + last_start as u64,
+                    last_line_hash: fingerprint(&tail[last_start..]),
+                    lines: lines_before + tail.iter().filter(|&&b| b == b'\n').count(),
+                }
+            }
+        };
+
+        cached.extend(parsed.iter().map(CachedEntry::from_entry));
+        let cache = CursorCache {
+            version: CACHE_VERSION,
+            path: path.to_string_lossy().to_string(),
+            format: format.to_string(),
+            parser_key: parser.cache_key(),
+            cursor,
+            entries: cached,
+        };
+        save_cache(&cache_path, &cache)?;
+
+        let parsed_count = parsed.len();
+        let cached_count = cache.entries.len() - parsed_count;
+        let mut entries: Vec<HistoryEntry> = cache
+            .entries
+            .into_iter()
+            .take(cached_count)
+            .map(CachedEntry::into_entry)
+            .collect();
+        entries.extend(parsed);
+
+        Ok(IncrementalRead {
+            entries,
+            parsed: parsed_count,
+            full_read,
+        })
+    }
+
+    /// Cursor file for a history file and format.
+    fn cache_path(&self, path: &Path, format: &str) -> PathBuf {
+        let key = fingerprint(format!("{}\0{}", format, path.to_string_lossy()).as_bytes());
+        self.cache_dir.join(format!("{:016x}.json", key))
+    }
+}
+
+impl CachedEntry {
+    /// Drop what the cache doesn't keep from a freshly parsed entry.
+    ///
+    /// The working directory has already become a project fingerprint;
+    /// no signal reads host names or referenced paths.
+    fn strip(entry: &mut HistoryEntry) {
+        entry.cwd = None;
+        entry.hostname = None;
+        entry.paths.clear();
+    }
+
+    fn from_entry(entry: &HistoryEntry) -> Self {
+        Self {
+            command: entry.command.clone(),
+            commands: entry.commands.clone(),
+            timestamp_ns: entry
+                .timestamp
+                .and_then(|ts| i64::try_from(ts.unix_timestamp_nanos()).ok()),
+            duration_ms: entry.duration.map(|d| d.whole_milliseconds() as i64),
+            exit_status: entry.exit_status,
+            session: entry.session.clone(),
+            project: entry.project,
+            steps: entry.steps.clone(),
+            line_hash: entry.line_hash,
+            line_number: entry.line_number,
+        }
+    }
+
+    fn into_entry(self) -> HistoryEntry {
+        let timestamp = self
+            .timestamp_ns
+            .and_then(|ns| OffsetDateTime::from_unix_timestamp_nanos(i128::from(ns)).ok());
+
+        let mut entry = HistoryEntry::new(self.command, timestamp, self.line_number);
+        entry.commands = self.commands;
+        entry.duration = self.duration_ms.map(Duration::milliseconds);
+        entry.exit_status = self.exit_status;
+        entry.session = self.session;
+        entry.project = self.project;
+        entry.steps = self.steps;
+        entry.line_hash = self.line_hash;
+        entry
+    }
+}
+
+/// Load a cursor cache, ignoring missing, corrupt or outdated files.
+fn load_cache(path: &Path) -> Option<CursorCache> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str::<CursorCache>(&content)
+        .ok()
+        .filter(|c| c.version == CACHE_VERSION)
+}
+
+/// Persist a cursor cache atomically (compact JSON, it can get large).
+fn save_cache(path: &Path, cache: &CursorCache) -> Result<()> {
+    let json = serde_json::to_string(cache).context("Failed to serialize history cursor")?;
+    absurd_core::fs_safety::write_atomic(path, &json)
+}
+
+/// Check that the line before the cursor is still the one we hashed.
+fn last_line_matches(file: &mut File, cursor: &Cursor) -> bool {
+    if cursor.offset == 0 {
+        return true;
+    }
+
+    let len = (cursor.offset - cursor.last_line_start) as usize;
+    let mut line = vec![0; len];
+
+    file.seek(SeekFrom::Start(cursor.last_line_start)).is_ok()
+        && file.read_exact(&mut line).is_ok()
+        && fingerprint(&line) == cursor.last_line_hash
+}
+
+/// File identity used to detect rotation.
+#[cfg(unix)]
+fn inode(metadata: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.ino()
+}
+
+/// File identity used to detect rotation (unavailable off Unix).
+#[cfg(not(unix))]
+fn inode(_metadata: &std::fs::Metadata) -> u64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{FishHistoryParser, HistoryFormat, ZshHistoryParser};
+    use std::io::Write;
+
+    struct Fixture {
+        dir: PathBuf,
+        history: PathBuf,
+    }
+
+    impl Fixture {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("absurdtty_test_cursor_{}", name));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            let history = dir.join(".zsh_history");
+            Self { dir, history }
+        }
+
+        fn append(&self, content: &str) {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.history)
+                .unwrap();
+            file.write_all(content.as_bytes()).unwrap();
+        }
+
+        fn read(&self) -> IncrementalRead {
+            let parser = ZshHistoryParser::new().preserve_full_lines(true);
+            self.read_as(HistoryFormat::Zsh, &parser)
+        }
+
+        fn read_as(&self, format: HistoryFormat, parser: &dyn HistoryParser) -> IncrementalRead {
+            let reader = IncrementalReader::with_dir(self.dir.join("cursors"));
+            let detection = Detection {
+                path: self.history.clone(),
+                format,
+                reason: "test".to_string(),
+            };
+            reader.read(&detection, parser).unwrap()
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[test]
+    fn only_appended_entries_are_parsed() {
+        let fixture = Fixture::new("append");
+        fixture.append(": 1702400000:0;git status\n: 1702400100:0;cargo build\n");
+
+        let first = fixture.read();
+        assert!(first.full_read);
+        assert_eq!(first.parsed, 2);
+
+        fixture.append(": 1702400200:3;cargo test\n");
+
+        let second = fixture.read();
+        assert!(!second.full_read);
+        assert_eq!(second.parsed, 1);
+        assert_eq!(second.entries.len(), 3);
+        assert_eq!(second.entries[2].command, "cargo");
+        assert_eq!(second.entries[2].line_number, 3);
+        assert_eq!(second.entries[2].duration, Some(Duration::seconds(3)));
+        // Cached entries keep their aggregates but not their lines or shapes
+        assert_eq!(second.entries[0].command, "git");
+        assert!(second.entries[0].full_line.is_empty());
+        assert_eq!(second.entries[0].line_hash, fingerprint(b"git status"));
+        assert!(second.entries[0].shape.is_none());
+        assert!(second.entries[2].shape.is_some());
+        assert_eq!(
+            second.entries[0].timestamp.unwrap().unix_timestamp(),
+            1702400000
+        );
+    }
+
+    #[test]
+    fn fresh_and_cached_entries_agree() {
+        let fixture = Fixture::new("fish");
+        let parser = FishHistoryParser::new();
+        fixture.append("- cmd: vim notes.md\n  when: 1702400000\n  paths:\n    - notes.md\n");
+
+        let first = fixture.read_as(HistoryFormat::Fish, &parser);
+        fixture.append("- cmd: ls\n  when: 1702400100\n");
+        let second = fixture.read_as(HistoryFormat::Fish, &parser);
+
+        // Paths are dropped whether the entry was just parsed or cached
+        assert!(first.entries[0].paths.is_empty());
+        assert!(second.entries[0].paths.is_empty());
+        assert_eq!(second.entries[0].command, "vim");
+    }
+
+    #[test]
+    fn unchanged_file_parses_nothing() {
+        let fixture = Fixture::new("unchanged");
+        fixture.append(": 1702400000:0;ls\n");
+
+        fixture.read();
+        let again = fixture.read();
+
+        assert_eq!(again.parsed, 0);
+        assert_eq!(again.entries.len(), 1);
+    }
+
+    #[test]
+    fn partial_trailing_line_waits_for_newline() {
+        let fixture = Fixture::new("partial");
+        fixture.append(": 1702400000:0;ls\n: 1702400100:0;gi");
+
+        assert_eq!(fixture.read().entries.len(), 1);
+
+        fixture.append("t status\n");
+        let second = fixture.read();
+        assert_eq!(second.parsed, 1);
+        assert_eq!(second.entries[1].command, "git");
+    }
+
+    #[test]
+    fn rewritten_file_is_read_in_full() {
+        let fixture = Fixture::new("rewrite");
+        fixture.append(": 1702400000:0;ls\n: 1702400100:0;pwd\n");
+        fixture.read();
+
+        // Same length, different content (e.g. history rewritten in place)
+        std::fs::write(&fixture.history, ": 1702400000:0;ls\n: 1702400100:0;cat\n").unwrap();
+
+        let second = fixture.read();
+        assert!(second.full_read);
+        assert_eq!(second.entries.len(), 2);
+        assert_eq!(second.entries[1].command, "cat");
+    }
+
+    #[test]
+    fn truncated_file_is_read_in_full() {
+        let fixture = Fixture::new("truncate");
+        fixture.append(": 1702400000:0;ls\n: 1702400100:0;pwd\n");
+        fixture.read();
+
+        std::fs::write(&fixture.history, ": 1702400200:0;cd\n").unwrap();
+
+        let second = fixture.read();
+        assert!(second.full_read);
+        assert_eq!(second.entries.len(), 1);
+        assert_eq!(second.entries[0].command, "cd");
+    }
+}
@@ -0,0 +1,423 @@
+//! History source detection.
+//!
+//! Finds the history file to analyze and the parser to read it with.
+//! Instead of trusting `$SHELL` (which names the login shell, not
+//! necessarily the interactive one), detection looks at:
+//!
+//! 1. `$HISTFILE`, if exported
+//! 2. `$ZDOTDIR` and the usual home/XDG locations of every supported
+//!    shell, preferring the most recently written file
+//! 3. The file contents themselves (zsh `: ts:dur;`, bash `#ts`,
+//!    fish `- cmd:`, asciicast and event log headers, SQLite table layout)
+//!
+//! The event log written by `tty-mood init` hooks is not a candidate:
+//! it only annotates shell history with exit statuses and durations
+//! (see [`events::annotate`](super::events::annotate)).
+//!
+//! Every detection carries a human-readable reason, recorded in the
+//! mood signature so the choice can be audited.
+
+use super::{events, HistoryFormat};
+use anyhow::Result;
+use rusqlite::{Connection, OpenFlags};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// How many bytes of a file to inspect when sniffing its format.
+const SNIFF_BYTES: u64 = 64 * 1024;
+
+/// Magic header at the start of every SQLite database file.
+pub const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// `--history` value that reads history from standard input.
+pub const STDIN: &str = "-";
+
+/// A history file together with the format it will be parsed as.
+#[derive(Debug, Clone)]
+pub struct Detection {
+    /// Path to the history file
+    pub path: PathBuf,
+    /// Format used to parse it
+    pub format: HistoryFormat,
+    /// Why this path and format were chosen
+    pub reason: String,
+}
+
+impl Detection {
+    /// Whether history is read from standard input rather than a file.
+    pub fn is_stdin(&self) -> bool {
+        self.path.as_os_str() == STDIN
+    }
+
+    /// The path as recorded in the signature (`<stdin>` for standard input).
+    pub fn display_path(&self) -> String {
+        if self.is_stdin() {
+            "<stdin>".to_string()
+        } else {
+            self.path.to_string_lossy().to_string()
+        }
+    }
+}
+
+/// Resolve the history source from optional CLI overrides.
+///
+/// - `path` is `-`: standard input, which needs an explicit format
+/// - `path` and `format` both given: used as-is
+/// - only `path`: the format is sniffed from the contents
+/// - only `format`: the default location for that format is used
+/// - neither: candidates are searched and sniffed
+pub fn resolve(path: Option<PathBuf>, format: Option<HistoryFormat>) -> Result<Detection> {
+    if path.as_deref().is_some_and(|p| p.as_os_str() == STDIN) {
+        let format = format.ok_or_else(|| {
+            anyhow::anyhow!("Reading history from stdin (--history -) requires --history-format")
+        })?;
+        return Ok(Detection {
+            path: PathBuf::from(STDIN),
+            format,
+            reason: "stdin; --history-format given".to_string(),
+        });
+    }
+
+    match (path, format) {
+        (Some(path), Some(format)) => Ok(Detection {
+            path,
+            format,
+            reason: "--history and --history-format given".to_string(),
+        }),
+        (Some(path), None) => {
+            let (format, why) = identify(&path);
+            Ok(Detection {
+                path,
+                format,
+                reason: format!("--history given; {}", why),
+            })
+        }
+        (None, Some(format)) => {
+            let path = default_locations(format)
+                .into_iter()
+                .find(|p| p.is_file())
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "No {} history found in its default locations. Use --history to specify path.",
+                        format.shell()
+                    )
+                })?;
+            Ok(Detection {
+                path,
+                format,
+                reason: format!("default location for --history-format {}", format.shell()),
+            })
+        }
+        (None, None) => detect().ok_or_else(|| {
+            anyhow::anyhow!("Could not detect shell history. Use --history to specify path.")
+        }),
+    }
+}
+
+/// Search for a history file without any user hints.
+pub fn detect() -> Option<Detection> {
+    // An exported HISTFILE is the most explicit signal there is
+    if let Some(path) = std::env::var_os("HISTFILE").map(PathBuf::from) {
+        if path.is_file() {
+            let (format, why) = identify(&path);
+            return Some(Detection {
+                path,
+                format,
+                reason: format!("$HISTFILE; {}", why),
+            });
+        }
+    }
+
+    // Otherwise prefer whichever known history was written most recently,
+    // which tracks the shell actually used interactively
+    let path = candidates()
+        .into_iter()
+        .filter(|p| p.is_file())
+        .max_by_key(|p| modified(p))?;
+
+    let (format, why) = identify(&path);
+    Some(Detection {
+        path,
+        format,
+        reason: format!("most recently written known history; {}", why),
+    })
+}
+
+/// Determine the format of a file, preferring its contents over its name.
+pub fn identify(path: &Path) -> (HistoryFormat, String) {
+    if let Some((format, why)) = sniff_file(path) {
+        return (format, format!("content: {}", why));
+    }
+
+    let format = HistoryFormat::from_path(path);
+    (format, format!("file name suggests {}", format.shell()))
+}
+
+/// Sniff the format of a history file from its first bytes.
+pub fn sniff_file(path: &Path) -> Option<(HistoryFormat, &'static str)> {
+    let mut head = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(SNIFF_BYTES)
+        .read_to_end(&mut head)
+        .ok()?;
+
+    if head.starts_with(SQLITE_HEADER) {
+        return sniff_sqlite(path);
+    }
+
+    sniff_content(&String::from_utf8_lossy(&head))
+}
+
+/// Sniff the format of text history content.
+///
+/// Returns None when the content has no format-specific markers
+/// (e.g. plain one-command-per-line history).
+pub fn sniff_content(content: &str) -> Option<(HistoryFormat, &'static str)> {
+    let first = content.lines().next();
+    if first.is_some_and(is_asciicast_header) {
+        return Some((HistoryFormat::Asciicast, "asciicast v2 header"));
+    }
+    if first == Some(events::HEADER) {
+        return Some((HistoryFormat::Events, "absurdtty event log header"));
+    }
+
+    let mut zsh = 0;
+    let mut bash = 0;
+    let mut fish = 0;
+
+    for line in content.lines().take(500) {
+        if is_zsh_extended(line) {
+            zsh += 1;
+        } else if is_bash_timestamp(line) {
+            bash += 1;
+        } else if line.starts_with("- cmd:") {
+            fish += 1;
+        }
+    }
+
+    let best = [
+        (zsh, HistoryFormat::Zsh, "zsh extended `: ts:dur;` lines"),
+        (bash, HistoryFormat::Bash, "bash `#ts` timestamp markers"),
+        (fish, HistoryFormat::Fish, "fish `- cmd:` records"),
+    ]
+    .into_iter()
+    .max_by_key(|(count, _, _)| *count)?;
+
+    (best.0 > 0).then_some((best.1, best.2))
+}
+
+/// Tell Atuin and nushell databases apart by their `history` columns.
+fn sniff_sqlite(path: &Path) -> Option<(HistoryFormat, &'static str)> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .ok()?;
+
+    let mut stmt = conn
+        .prepare("SELECT name FROM pragma_table_info('history')")
+        .ok()?;
+    let columns: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .ok()?
+        .filter_map(|c| c.ok())
+        .collect();
+
+    if columns.iter().any(|c| c == "command_line") {
+        Some((HistoryFormat::Nushell, "SQLite table with nushell columns"))
+    } else if columns.iter().any(|c| c == "exit") {
+        Some((HistoryFormat::Atuin, "SQLite table with Atuin columns"))
+    } else {
+        None
+    }
+}
+
+/// Check for zsh extended format: `: TIMESTAMP:DURATION;COMMAND`.
+fn is_zsh_extended(line: &str) -> bool {
+    let Some(rest) = line.strip_prefix(": ") else {
+        return false;
+    };
+    let Some((metadata, _)) = rest.split_once(';') else {
+        return false;
+    };
+    let Some((timestamp, duration)) = metadata.split_once(':') else {
+        return false;
+    };
+
+    is_digits(timestamp) && is_digits(duration)
+}
+
+/// Check for an asciicast v2 header: `{"version": 2, ...}`.
+fn is_asciicast_header(line: &str) -> bool {
+    line.starts_with('{')
+        && serde_json::from_str::<serde_json::Value>(line)
+            .is_ok_and(|header| header.get("version").and_then(|v| v.as_u64()) == Some(2))
+}
+
+/// Check for a bash `#TIMESTAMP` marker.
+fn is_bash_timestamp(line: &str) -> bool {
+    line.strip_prefix('#').is_some_and(is_digits)
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Every default history location we know about.
+fn candidates() -> Vec<PathBuf> {
+    [
+        HistoryFormat::Zsh,
+        HistoryFormat::Bash,
+        HistoryFormat::Fish,
+        HistoryFormat::Nushell,
+        HistoryFormat::PowerShell,
+        HistoryFormat::Atuin,
+    ]
+    .into_iter()
+    .flat_map(default_locations)
+    .collect()
+}
+
+/// Default history locations for a format, most specific first.
+pub fn default_locations(format: HistoryFormat) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let home = dirs::home_dir();
+
+    match format {
+        HistoryFormat::Zsh => {
+            if let Some(zdotdir) = std::env::var_os("ZDOTDIR") {
+                paths.push(PathBuf::from(zdotdir).join(".zsh_history"));
+            }
+            if let Some(home) = &home {
+                paths.push(home.join(".zsh_history"));
+                paths.push(home.join(".histfile"));
+            }
+        }
+        HistoryFormat::Bash => {
+            if let Some(home) = &home {
+                paths.push(home.join(".bash_history"));
+            }
+        }
+        HistoryFormat::Fish => {
+            if let Some(data) = dirs::data_dir() {
+                paths.push(data.join("fish/fish_history"));
+            }
+        }
+        HistoryFormat::Atuin => {
+            if let Some(data) = dirs::data_dir() {
+                paths.push(data.join("atuin/history.db"));
+            }
+        }
+        HistoryFormat::Nushell => {
+            if let Some(config) = dirs::config_dir() {
+                paths.push(config.join("nushell/history.sqlite3"));
+                paths.push(config.join("nushell/history.txt"));
+            }
+        }
+        HistoryFormat::PowerShell => {
+            if let Some(data) = dirs::data_dir() {
+                paths.push(data.join("powershell/PSReadLine/ConsoleHost_history.txt"));
+            }
+        }
+        // Recordings live wherever they were saved
+        HistoryFormat::Asciicast => {}
+        HistoryFormat::Events => {
+            if let Some(data) = dirs::data_dir() {
+                paths.push(data.join("absurdtty/events.log"));
+            }
+        }
+    }
+
+    paths
+}
+
+/// Modification time of a file, oldest possible if unknown.
+fn modified(path: &Path) -> SystemTime {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff_zsh_extended() {
+        let content = ": 1702400000:0;git status\n: 1702400100:3;cargo build\n";
+        assert_eq!(sniff_content(content).unwrap().0, HistoryFormat::Zsh);
+    }
+
+    #[test]
+    fn sniff_bash_timestamps() {
+        let content = "#1702400000\ngit status\n#1702400100\ncargo build\n";
+        assert_eq!(sniff_content(content).unwrap().0, HistoryFormat::Bash);
+    }
+
+    #[test]
+    fn sniff_fish_records() {
+        let content = "- cmd: git status\n  when: 1702400000\n";
+        assert_eq!(sniff_content(content).unwrap().0, HistoryFormat::Fish);
+    }
+
+    #[test]
+    fn sniff_asciicast_header() {
+        let content = "{\"version\": 2, \"width\": 80, \"height\": 24}\n[0.5, \"o\", \"$ \"]\n";
+        assert_eq!(sniff_content(content).unwrap().0, HistoryFormat::Asciicast);
+        assert!(sniff_content("{\"version\": 1}\n").is_none());
+    }
+
+    #[test]
+    fn sniff_event_log_header() {
+        let content = format!("{}\n1702400000\t12\t0\t1\tls\n", events::HEADER);
+        assert_eq!(sniff_content(&content).unwrap().0, HistoryFormat::Events);
+    }
+
+    #[test]
+    fn sniff_plain_history_is_inconclusive() {
+        assert!(sniff_content("ls -la\ncd ..\n# a comment\n").is_none());
+        assert!(sniff_content("").is_none());
+    }
+
+    #[test]
+    fn zsh_marker_requires_numeric_metadata() {
+        assert!(is_zsh_extended(": 1702400000:0;ls"));
+        assert!(!is_zsh_extended(": not:a;timestamp"));
+        assert!(!is_zsh_extended(": 1702400000;ls"));
+    }
+
+    #[test]
+    fn explicit_path_and_format_are_trusted() {
+        let detection = resolve(
+            Some(PathBuf::from("/nonexistent/history")),
+            Some(HistoryFormat::Fish),
+        )
+        .unwrap();
+
+        assert_eq!(detection.format, HistoryFormat::Fish);
+        assert!(detection.reason.contains("--history-format"));
+    }
+
+    #[test]
+    fn stdin_needs_a_format() {
+        assert!(resolve(Some(PathBuf::from("-")), None).is_err());
+
+        let detection = resolve(Some(PathBuf::from("-")), Some(HistoryFormat::Zsh)).unwrap();
+        assert!(detection.is_stdin());
+        assert_eq!(detection.display_path(), "<stdin>");
+    }
+
+    #[test]
+    fn explicit_path_is_sniffed() {
+        let path = std::env::temp_dir().join("absurdtty_test_sniff_history");
+        std::fs::write(&path, "#1702400000\nls\n").unwrap();
+
+        let detection = resolve(Some(path.clone()), None).unwrap();
+        assert_eq!(detection.format, HistoryFormat::Bash);
+        assert!(detection.reason.contains("content"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
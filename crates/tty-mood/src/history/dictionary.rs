@@ -0,0 +1,446 @@
+//! Known-command dictionary for typo detection.
+//!
+//! A command is a typo when it is not something the shell could run,
+//! but is one or two keystrokes away from something it could. The
+//! dictionary holds every executable in `$PATH` plus shell builtins.
+//!
+//! Scanning `$PATH` takes a moment, so the dictionary is cached in
+//! `~/.local/share/absurdtty/commands.json` and rebuilt only when
+//! `$PATH` or one of its directories changes.
+//!
+//! The likely intended command of a typo is kept on the entry for
+//! internal analysis only; it is never written to the mood signature.
+
+use super::HistoryEntry;
+use absurd_core::Paths;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Bump when the cache layout changes; old caches are then rebuilt.
+const CACHE_VERSION: u32 = 1;
+
+/// Builtins and reserved words of bash, zsh and fish.
+const BUILTINS: &[&str] = &[
+    ".",
+    ":",
+    "[",
+    "[[",
+    "alias",
+    "autoload",
+    "bg",
+    "bind",
+    "bindkey",
+    "break",
+    "builtin",
+    "caller",
+    "cd",
+    "chdir",
+    "command",
+    "commandline",
+    "compdef",
+    "complete",
+    "contains",
+    "continue",
+    "declare",
+    "dirs",
+    "disown",
+    "echo",
+    "emulate",
+    "enable",
+    "eval",
+    "exec",
+    "exit",
+    "export",
+    "false",
+    "fc",
+    "fg",
+    "functions",
+    "getopts",
+    "hash",
+    "help",
+    "history",
+    "jobs",
+    "kill",
+    "let",
+    "local",
+    "logout",
+    "popd",
+    "print",
+    "printf",
+    "pushd",
+    "pwd",
+    "read",
+    "readonly",
+    "rehash",
+    "return",
+    "set",
+    "setopt",
+    "shift",
+    "shopt",
+    "source",
+    "status",
+    "string",
+    "suspend",
+    "test",
+    "times",
+    "trap",
+    "true",
+    "type",
+    "typeset",
+    "ulimit",
+    "umask",
+    "unalias",
+    "unfunction",
+    "unset",
+    "unsetopt",
+    "wait",
+    "whence",
+    "where",
+    "which",
+    "zle",
+    "zmodload",
+    "zstyle",
+    "abbr",
+    "funced",
+    "funcsave",
+    "math",
+    "set_color",
+    "argparse",
+    "count",
+    "random",
+    "realpath",
+];
+
+/// Result of looking a command up in the dictionary.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CommandLookup {
+    /// Not checked (no dictionary available)
+    #[default]
+    Unchecked,
+    /// A known executable or builtin, or a path
+    Known,
+    /// Unknown, but close to a known command
+    Typo {
+        /// The command that was probably meant (internal use only)
+        intended: String,
+    },
+    /// Unknown and not close to anything (a project script, another machine)
+    Unknown,
+}
+
+/// Executables in `$PATH` plus shell builtins.
+#[derive(Debug, Default)]
+pub struct CommandDictionary {
+    commands: HashSet<String>,
+}
+
+/// Cached dictionary, keyed by the state of `$PATH`.
+#[derive(Debug, Serialize, Deserialize)]
+struct DictionaryCache {
+    version: u32,
+    /// Fingerprint of `$PATH` and the modification times of its directories
+    path_state: u64,
+    commands: Vec<String>,
+}
+
+impl CommandDictionary {
+    /// Build a dictionary from a list of command names (plus builtins).
+    pub fn with_commands<I, S>(commands: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut commands: HashSet<String> = commands.into_iter().map(Into::into).collect();
+        commands.extend(BUILTINS.iter().map(|b| b.to_string()));
+        Self { commands }
+    }
+
+    /// Load the dictionary for the current `$PATH`, from cache if still valid.
+    pub fn load() -> Result<Self> {
+        let path_var = std::env::var_os("PATH").unwrap_or_default();
+        let cache_path = Paths::data_dir()?.join("commands.json");
+        let state = path_state(&path_var);
+
+        let cached = std::fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<DictionaryCache>(&content).ok())
+            .filter(|c| c.version == CACHE_VERSION && c.path_state == state);
+
+        if let Some(cache) = cached {
+            return Ok(Self::with_commands(cache.commands));
+        }
+
+        let mut commands: Vec<String> = scan_path(&path_var).into_iter().collect();
+        commands.sort();
+
+        let cache = DictionaryCache {
+            version: CACHE_VERSION,
+            path_state: state,
+            commands,
+        };
+        // A failed cache write only costs a rescan next time
+        if let Ok(json) = serde_json::to_string(&cache) {
+            let _ = absurd_core::fs_safety::write_atomic(&cache_path, &json);
+        }
+
+        Ok(Self::with_commands(cache.commands))
+    }
+
+    /// Add more known names (e.g. aliases and functions).
+    pub fn extend<I: IntoIterator<Item = String>>(&mut self, names: I) {
+        self.commands.extend(names);
+    }
+
+    /// Whether a command name is known.
+    pub fn contains(&self, command: &str) -> bool {
+        self.commands.contains(command)
+    }
+
+    /// Look up a command name.
+    pub fn lookup(&self, command: &str) -> CommandLookup {
+        // Paths, expansions and comments can't be judged by name
+        if self.contains(command)
+            || command.is_empty()
+            || command.contains(['/', '$', '=', '#', '~', '*'])
+        {
+            return CommandLookup::Known;
+        }
+
+        // A single character is one slip away from every other one
+        if command.chars().count() < 2 {
+            return CommandLookup::Unknown;
+        }
+
+        match self.nearest(command) {
+            Some(intended) => CommandLookup::Typo { intended },
+            None => CommandLookup::Unknown,
+        }
+    }
+
+    /// The closest known command within the typo distance, if any.
+    ///
+    /// Ties are broken alphabetically so results are stable.
+    fn nearest(&self, command: &str) -> Option<String> {
+        let len = command.chars().count();
+        // One slip for short names, two for longer ones
+        let max_distance = if len <= 4 { 1 } else { 2 };
+
+        self.commands
+            .iter()
+            .filter(|known| known.chars().count().abs_diff(len) <= max_distance)
+            .filter_map(|known| {
+                let distance = edit_distance(command, known);
+                (distance <= max_distance).then_some((distance, known))
+            })
+            .min()
+            .map(|(_, known)| known.clone())
+    }
+
+    /// Check the command of every entry, looking each name up once.
+    pub fn check_all(&self, entries: &mut [HistoryEntry]) {
+        if !self
+            .commands
+            .iter()
+            .any(|c| !BUILTINS.contains(&c.as_str()))
+        {
+            // Nothing found in $PATH; better not to judge at all
+            return;
+        }
+
+        let mut seen: HashMap<String, CommandLookup> = HashMap::new();
+
+        for entry in entries {
+            let name = entry.command_name().to_string();
+            let lookup = seen
+                .entry(name)
+                .or_insert_with_key(|name| self.lookup(name));
+            entry.lookup = lookup.clone();
+        }
+    }
+}
+
+/// Collect executable file names from every directory in `$PATH`.
+fn scan_path(path_var: &std::ffi::OsStr) -> HashSet<String> {
+    let mut commands = HashSet::new();
+
+    for dir in std::env::split_paths(path_var) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            if is_executable(&entry.path()) {
+                commands.insert(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+
+    commands
+}
+
+/// Whether a path is an executable file (following symlinks).
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+/// Whether a path is an executable file (following symlinks).
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|m| m.is_file())
+}
+
+/// Fingerprint of `$PATH` and its directories' modification times.
+///
+/// Installing or removing a program touches its directory, which
+/// invalidates the cache.
+fn path_state(path_var: &std::ffi::OsStr) -> u64 {
+    let mut state = path_var.to_string_lossy().into_owned();
+
+    for dir in std::env::split_paths(path_var) {
+        let modified = std::fs::metadata(&dir)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos());
+        state.push_str(&format!("\0{}", modified));
+    }
+
+    super::fingerprint(state.as_bytes())
+}
+
+/// Edit distance counting insertions, deletions, substitutions and
+/// swaps of adjacent characters (`gti` is one swap from `git`).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // d[i][j] = distance between a[..i] and b[..j]
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dictionary() -> CommandDictionary {
+        CommandDictionary::with_commands(["git", "cargo", "xz", "jq", "fd", "rg", "ls", "make"])
+    }
+
+    #[test]
+    fn edit_distance_counts_swaps() {
+        assert_eq!(edit_distance("gti", "git"), 1);
+        assert_eq!(edit_distance("carg", "cargo"), 1);
+        assert_eq!(edit_distance("cargo", "cargo"), 0);
+        assert_eq!(edit_distance("ls", "make"), 4);
+    }
+
+    #[test]
+    fn short_real_tools_are_known() {
+        let dict = dictionary();
+        for tool in ["xz", "jq", "fd", "rg", "cd", "echo"] {
+            assert_eq!(dict.lookup(tool), CommandLookup::Known, "{}", tool);
+        }
+    }
+
+    #[test]
+    fn near_misses_are_typos() {
+        let dict = dictionary();
+        assert_eq!(
+            dict.lookup("gti"),
+            CommandLookup::Typo {
+                intended: "git".to_string()
+            }
+        );
+        assert_eq!(
+            dict.lookup("carg"),
+            CommandLookup::Typo {
+                intended: "cargo".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn unrelated_unknowns_are_not_typos() {
+        let dict = dictionary();
+        assert_eq!(dict.lookup("deploy-staging"), CommandLookup::Unknown);
+        assert_eq!(dict.lookup("k"), CommandLookup::Unknown);
+        assert_eq!(dict.lookup("./build.sh"), CommandLookup::Known);
+    }
+
+    #[test]
+    fn alias_names_extend_the_dictionary() {
+        let mut dict = dictionary();
+        dict.extend(["gst".to_string()]);
+
+        assert_eq!(dict.lookup("gst"), CommandLookup::Known);
+        assert_eq!(
+            dict.lookup("gts"),
+            CommandLookup::Typo {
+                intended: "gst".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn check_all_sets_lookup() {
+        let dict = dictionary();
+        let mut entries = vec![
+            HistoryEntry::new("gti status".into(), None, 1),
+            HistoryEntry::new("jq .".into(), None, 2),
+        ];
+
+        dict.check_all(&mut entries);
+
+        assert!(entries[0].looks_like_typo());
+        assert_eq!(entries[0].intended_command(), Some("git"));
+        assert!(!entries[1].looks_like_typo());
+    }
+
+    #[test]
+    fn path_scan_finds_executables() {
+        let dir = std::env::temp_dir().join("absurdtty_test_dictionary_path");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("mytool"), "#!/bin/sh\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let executable = std::fs::Permissions::from_mode(0o755);
+            std::fs::set_permissions(dir.join("mytool"), executable).unwrap();
+        }
+
+        let found = scan_path(dir.as_os_str());
+        assert!(found.contains("mytool"));
+        #[cfg(unix)]
+        assert!(!found.contains("notes.txt"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
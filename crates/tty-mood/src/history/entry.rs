@@ -2,19 +2,58 @@
 //!
 //! A single command from shell history with metadata.
 
-use time::OffsetDateTime;
+use super::dictionary::CommandLookup;
+use super::lexer::LineShape;
+use time::{Duration, OffsetDateTime};
+
+/// Tools whose first argument names what they do (`git add`, `cargo test`).
+const SUBCOMMAND_TOOLS: &[&str] = &[
+    "git", "cargo", "npm", "pnpm", "yarn", "docker", "kubectl", "go", "make", "just",
+    "systemctl", "brew", "apt", "pip",
+];
 
 /// A single entry from shell history.
 #[derive(Debug, Clone)]
 pub struct HistoryEntry {
     /// The command that was executed (without arguments for privacy)
     pub command: String,
+    /// Every command the line invokes (pipelines, lists, substitutions)
+    pub commands: Vec<String>,
+    /// Alias or function the line was typed through (after resolution)
+    pub alias: Option<String>,
+    /// Whether the command is known, unknown or a likely typo
+    pub lookup: CommandLookup,
     /// Full command line (if available, for internal analysis only)
     pub full_line: String,
+    /// Stable hash of the full command line (survives caching, unlike `full_line`)
+    pub line_hash: u64,
+    /// Structure of the full command line (holds no text; not cached,
+    /// so entries from the history cursor have none)
+    pub shape: Option<LineShape>,
+    /// Stable hash of each invoked command and, for tools like git, its
+    /// subcommand (the steps of a ritual; survives caching, holds no text)
+    pub steps: Vec<u64>,
     /// When the command was executed
     pub timestamp: Option<OffsetDateTime>,
-    /// Duration in seconds (if available)
-    pub duration: Option<u64>,
+    /// How long the command ran (if available)
+    pub duration: Option<Duration>,
+    /// Exit status (if the source records it)
+    pub exit_status: Option<i32>,
+    /// Working directory the command ran in (if available)
+    pub cwd: Option<String>,
+    /// Fingerprint of the project root the command ran in (never its path)
+    pub project: Option<u64>,
+    /// Host the command ran on (if available)
+    pub hostname: Option<String>,
+    /// Shell session identifier (if available)
+    pub session: Option<String>,
+    /// Paths referenced by the command (recorded by fish)
+    pub paths: Vec<String>,
+    /// Pauses between keystrokes while typing, ending with Enter
+    /// (recorded by asciinema with `--stdin`)
+    pub keystroke_gaps: Vec<Duration>,
+    /// Index of the source this entry came from (when merging several)
+    pub source: usize,
     /// Line number in history file (for debugging)
     #[allow(dead_code)]
     pub line_number: usize,
@@ -23,17 +62,70 @@ pub struct HistoryEntry {
 impl HistoryEntry {
     /// Create a new history entry.
     pub fn new(command: String, timestamp: Option<OffsetDateTime>, line_number: usize) -> Self {
+        let mut words = command.split_whitespace();
+        let commands: Vec<String> = words
+            .next()
+            .map(|name| vec![name.to_string()])
+            .unwrap_or_default();
+        let steps = commands
+            .iter()
+            .map(|name| step_hash(name, words.next()))
+            .collect();
+
         Self {
             command,
+            commands,
+            alias: None,
+            lookup: CommandLookup::Unchecked,
             full_line: String::new(),
+            line_hash: 0,
+            shape: None,
+            steps,
             timestamp,
             duration: None,
+            exit_status: None,
+            cwd: None,
+            project: None,
+            hostname: None,
+            session: None,
+            paths: Vec::new(),
+            keystroke_gaps: Vec::new(),
+            source: 0,
             line_number,
         }
     }
 
+    /// Create an entry from a full command line.
+    ///
+    /// The line is lexed to find every invoked command; the first one
+    /// becomes `command`. Comment-only lines keep the comment as command.
+    pub fn from_command_line(
+        line: &str,
+        timestamp: Option<OffsetDateTime>,
+        line_number: usize,
+    ) -> Self {
+        let lexed = super::lexer::lex(line);
+        let command = lexed
+            .commands
+            .first()
+            .cloned()
+            .unwrap_or_else(|| line.trim().to_string());
+
+        let mut entry = Self::new(command, timestamp, line_number);
+        entry.steps = lexed
+            .commands
+            .iter()
+            .zip(&lexed.arguments)
+            .map(|(name, argument)| step_hash(name, argument.as_deref()))
+            .collect();
+        entry.commands = lexed.commands;
+        entry.shape = Some(lexed.shape);
+        entry
+    }
+
     /// Create an entry with full command line preserved.
     pub fn with_full_line(mut self, full_line: String) -> Self {
+        self.line_hash = super::fingerprint(full_line.as_bytes());
         self.full_line = full_line;
         self
     }
@@ -46,11 +138,36 @@ impl HistoryEntry {
             .unwrap_or(&self.command)
     }
 
-    /// Check if this looks like a typo (very short, uncommon command).
+    /// Check if this looks like a typo.
+    ///
+    /// With a command dictionary, a typo is an unknown command close to
+    /// a known one. Without, very short uncommon commands are suspect.
+    /// Commands typed through a known alias or function are never typos.
     pub fn looks_like_typo(&self) -> bool {
-        let cmd = self.command_name();
-        // Common typos are short and don't match known commands
-        cmd.len() <= 3 && !Self::is_known_short_command(cmd)
+        if self.alias.is_some() {
+            return false;
+        }
+
+        match self.lookup {
+            CommandLookup::Typo { .. } => true,
+            CommandLookup::Known | CommandLookup::Unknown => false,
+            CommandLookup::Unchecked => {
+                let cmd = self.command_name();
+                // Common typos are short and don't match known commands.
+                // Count characters, not bytes: `日本語` is not a three-letter typo.
+                cmd.chars().count() <= 3 && !Self::is_known_short_command(cmd)
+            }
+        }
+    }
+
+    /// The command that was probably meant, if this is a typo.
+    ///
+    /// For internal analysis only; never written to the signature.
+    pub fn intended_command(&self) -> Option<&str> {
+        match &self.lookup {
+            CommandLookup::Typo { intended } => Some(intended),
+            _ => None,
+        }
     }
 
     /// Check if command is a known short command.
@@ -64,7 +181,24 @@ impl HistoryEntry {
         )
     }
 
+    /// Check if the command is known to have failed.
+    ///
+    /// Interrupted commands (Ctrl-C, exit status 130) are not failures.
+    pub fn failed(&self) -> bool {
+        matches!(self.exit_status, Some(code) if code != 0 && code != 130)
+    }
+
+    /// How long the command took to type, from first key to Enter.
+    ///
+    /// Zero unless the source recorded keystrokes.
+    pub fn typing_time(&self) -> Duration {
+        self.keystroke_gaps.iter().sum()
+    }
+
     /// Get hour of day (0-23) if timestamp available.
+    ///
+    /// Hours are read in the timestamp's own offset: UTC as parsed,
+    /// local time once moved into the user's zone.
     pub fn hour(&self) -> Option<u8> {
         self.timestamp.map(|ts| ts.hour())
     }
@@ -95,6 +229,17 @@ impl HistoryEntry {
     }
 }
 
+/// Hash a command's name, plus its first argument for [`SUBCOMMAND_TOOLS`].
+pub fn step_hash(name: &str, argument: Option<&str>) -> u64 {
+    let step = match argument {
+        Some(sub) if SUBCOMMAND_TOOLS.contains(&name) && !sub.starts_with('-') => {
+            format!("{} {}", name, sub)
+        }
+        _ => name.to_string(),
+    };
+    super::fingerprint(step.as_bytes())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,6 +250,63 @@ mod tests {
         assert_eq!(entry.command_name(), "git");
     }
 
+    #[test]
+    fn from_command_line_finds_all_commands() {
+        let entry = HistoryEntry::from_command_line("cat x | grep y && cargo test", None, 1);
+        assert_eq!(entry.command, "cat");
+        assert_eq!(entry.commands, ["cat", "grep", "cargo"]);
+
+        let comment = HistoryEntry::from_command_line("# note to self", None, 1);
+        assert_eq!(comment.command, "# note to self");
+        assert!(comment.commands.is_empty());
+    }
+
+    #[test]
+    fn steps_include_subcommands_of_known_tools() {
+        let add = HistoryEntry::from_command_line("git add -p src/main.rs", None, 1);
+        let commit = HistoryEntry::from_command_line("git commit -m 'wip'", None, 1);
+        assert_ne!(add.steps, commit.steps);
+        assert_eq!(
+            add.steps,
+            HistoryEntry::new("git add".into(), None, 1).steps
+        );
+
+        let vim = HistoryEntry::from_command_line("vim notes.md", None, 1);
+        assert_eq!(vim.steps, HistoryEntry::new("vim".into(), None, 1).steps);
+    }
+
+    #[test]
+    fn one_step_per_invoked_command() {
+        let step = |line: &str| HistoryEntry::new(line.into(), None, 1).steps[0];
+
+        let sudo = HistoryEntry::from_command_line("sudo git push", None, 1);
+        assert_eq!(sudo.steps, [step("git push")]);
+
+        let env = HistoryEntry::from_command_line("FOO=1 cargo test", None, 1);
+        assert_eq!(env.steps, [step("cargo test")]);
+
+        let chain = HistoryEntry::from_command_line("git add . && git commit && git push", None, 1);
+        assert_eq!(
+            chain.steps,
+            [step("git add"), step("git commit"), step("git push")]
+        );
+
+        let comment = HistoryEntry::from_command_line("# note", None, 1);
+        assert!(comment.steps.is_empty());
+    }
+
+    #[test]
+    fn interrupted_commands_are_not_failures() {
+        let mut entry = HistoryEntry::new("cargo".into(), None, 1);
+        assert!(!entry.failed());
+
+        entry.exit_status = Some(101);
+        assert!(entry.failed());
+
+        entry.exit_status = Some(130);
+        assert!(!entry.failed());
+    }
+
     #[test]
     fn known_short_commands_not_typos() {
         let entry = HistoryEntry::new("ls".into(), None, 1);
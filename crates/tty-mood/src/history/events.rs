@@ -0,0 +1,241 @@
+//! absurdtty event log parser.
+//!
+//! The shell hooks printed by `tty-mood init` append one tab-separated
+//! line per command to `~/.local/share/absurdtty/events.log` (tabs
+//! shown as `→`):
+//!
+//! ```text
+//! # absurdtty events v1
+//! 1702400000.123456→1520→0→3735928559→cargo
+//! 1702400004.5→12→127→3735928559→gti
+//! ```
+//!
+//! Fields are the start time (Unix seconds, fractional), duration in
+//! milliseconds, exit status, a checksum of the working directory and
+//! the first word as typed. Unlike shell history files, the log records
+//! whether each command failed; it never records arguments or paths.
+//!
+//! Since it knows nothing else, the log supplements shell history
+//! rather than replacing it: [`annotate`] copies each event onto the
+//! history entry started in the same second with the same first word.
+
+use super::{entry::HistoryEntry, lexer, HistoryParser};
+use anyhow::{Context, Result};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use time::{Duration, OffsetDateTime};
+
+/// First line of every event log the hooks write to.
+pub const HEADER: &str = "# absurdtty events v1";
+
+/// Parser for the hook-written event log.
+#[derive(Debug, Default)]
+pub struct EventLogParser {
+    /// Whether to preserve full command lines (for internal analysis)
+    preserve_full_line: bool,
+}
+
+impl EventLogParser {
+    /// Create a new event log parser.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure whether to preserve full command lines.
+    ///
+    /// The log only holds command names, so this keeps just the name.
+    pub fn preserve_full_lines(mut self, preserve: bool) -> Self {
+        self.preserve_full_line = preserve;
+        self
+    }
+
+    /// Parse a single event line; malformed lines are skipped.
+    fn parse_line(&self, line: &str, line_number: usize) -> Option<HistoryEntry> {
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let mut fields = line.splitn(5, '\t');
+        let start = parse_start(fields.next()?)?;
+        let duration_ms: i64 = fields.next()?.parse().ok()?;
+        let exit_status: i32 = fields.next()?.parse().ok()?;
+        let cwd = fields.next()?;
+        let command = fields.next()?.trim();
+
+        if command.is_empty() {
+            return None;
+        }
+
+        let mut entry = HistoryEntry::from_command_line(command, Some(start), line_number);
+        entry.duration = Some(Duration::milliseconds(duration_ms.max(0)));
+        entry.exit_status = Some(exit_status);
+        entry.cwd = (!cwd.is_empty()).then(|| cwd.to_string());
+        // A bare command name says nothing about the line's shape
+        entry.shape = None;
+
+        if self.preserve_full_line {
+            entry = entry.with_full_line(command.to_string());
+        }
+
+        Some(entry)
+    }
+
+    fn parse_content(&self, content: &str) -> Vec<HistoryEntry> {
+        content
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| self.parse_line(line, index + 1))
+            .collect()
+    }
+}
+
+impl HistoryParser for EventLogParser {
+    fn parse_file(&self, path: &Path) -> Result<Vec<HistoryEntry>> {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("Failed to read event log: {:?}", path))?;
+        Ok(self.parse_content(&String::from_utf8_lossy(&bytes)))
+    }
+
+    fn parse_str(&self, content: &str) -> Result<Vec<HistoryEntry>> {
+        Ok(self.parse_content(content))
+    }
+}
+
+/// Copy exit status and duration from logged events onto history.
+///
+/// An entry matches an event started in the same second with the same
+/// first word as typed; repeats within a second pair up in order.
+/// Events need their full line kept. Returns how many entries were
+/// annotated.
+///
+/// The cwd checksum is left out: it names a directory, not a project,
+/// and projects are resolved while parsing, before this runs.
+pub fn annotate(entries: &mut [HistoryEntry], events: &[HistoryEntry]) -> usize {
+    let mut pending: HashMap<(i64, String), VecDeque<&HistoryEntry>> = HashMap::new();
+    for event in events {
+        if let Some(ts) = event.timestamp {
+            pending
+                .entry((ts.unix_timestamp(), typed_name(event)))
+                .or_default()
+                .push_back(event);
+        }
+    }
+
+    let mut annotated = 0;
+    for entry in entries.iter_mut() {
+        let Some(ts) = entry.timestamp else {
+            continue;
+        };
+        let Some(event) = pending
+            .get_mut(&(ts.unix_timestamp(), typed_name(entry)))
+            .and_then(VecDeque::pop_front)
+        else {
+            continue;
+        };
+
+        entry.exit_status = event.exit_status;
+        entry.duration = event.duration;
+        annotated += 1;
+    }
+
+    annotated
+}
+
+/// The first word of an entry's line as typed.
+///
+/// The hooks log `sudo`, `FOO=1` and aliases as typed, where `command`
+/// has them stripped or expanded. Cached entries keep no line, so they
+/// fall back to `command`.
+fn typed_name(entry: &HistoryEntry) -> String {
+    lexer::split_words(&entry.full_line)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| entry.command.clone())
+}
+
+/// Parse `1702400000` or `1702400000.123456` as a UTC instant.
+fn parse_start(value: &str) -> Option<OffsetDateTime> {
+    let (secs, frac) = value.split_once('.').unwrap_or((value, ""));
+    let secs: i64 = secs.parse().ok()?;
+
+    if !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let nanos = format!("{:0<9}", &frac[..frac.len().min(9)])
+        .parse::<i64>()
+        .ok()?;
+
+    OffsetDateTime::from_unix_timestamp(secs)
+        .ok()
+        .map(|ts| ts + Duration::nanoseconds(nanos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_events() {
+        let content = format!(
+            "{}\n1702400000.25\t1520\t0\t3735928559\tcargo\n1702400004\t12\t127\t3735928559\tgti\n",
+            HEADER
+        );
+        let entries = EventLogParser::new().parse_content(&content);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].command, "cargo");
+        assert_eq!(
+            entries[0].timestamp.unwrap().unix_timestamp_nanos(),
+            1_702_400_000_250_000_000
+        );
+        assert_eq!(entries[0].duration, Some(Duration::milliseconds(1520)));
+        assert_eq!(entries[0].cwd.as_deref(), Some("3735928559"));
+        assert_eq!(entries[1].exit_status, Some(127));
+        assert!(entries[1].failed());
+        assert_eq!(entries[1].line_number, 3);
+    }
+
+    #[test]
+    fn events_annotate_history() {
+        let content = format!(
+            "{}\n1702400000.9\t1520\t0\t1\tcargo\n1702400003.2\t12\t127\t1\tgti\n1702400003.7\t8\t1\t1\tgti\n1702400006\t9\t1\t1\tsudo\n1702400007\t9\t2\t1\tFOO=1\n",
+            HEADER
+        );
+        let events = EventLogParser::new()
+            .preserve_full_lines(true)
+            .parse_content(&content);
+        let at = |secs| OffsetDateTime::from_unix_timestamp(secs).ok();
+        let typed = |line: &str, secs| {
+            HistoryEntry::from_command_line(line, at(secs), 6).with_full_line(line.to_string())
+        };
+        let mut entries = vec![
+            HistoryEntry::from_command_line("cargo build --release", at(1_702_400_000), 1),
+            HistoryEntry::from_command_line("gti status", at(1_702_400_003), 2),
+            HistoryEntry::from_command_line("gti push", at(1_702_400_003), 3),
+            HistoryEntry::from_command_line("gti log", at(1_702_400_003), 4),
+            HistoryEntry::from_command_line("ls", at(1_702_400_005), 5),
+            typed("sudo pacman -Syu", 1_702_400_006),
+            typed("FOO=1 make", 1_702_400_007),
+        ];
+
+        assert_eq!(annotate(&mut entries, &events), 5);
+        assert_eq!(entries[0].duration, Some(Duration::milliseconds(1520)));
+        assert!(entries[0].cwd.is_none());
+        assert!(entries[0].shape.is_some());
+        assert_eq!(entries[1].exit_status, Some(127));
+        assert_eq!(entries[2].exit_status, Some(1));
+        assert_eq!(entries[3].exit_status, None);
+        assert_eq!(entries[4].exit_status, None);
+        assert_eq!(entries[5].exit_status, Some(1));
+        assert_eq!(entries[6].exit_status, Some(2));
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        let content = "garbage\n1702400000\tx\t0\t1\tls\n1702400000\t5\t0\t1\t\n1702400000.1e3\t5\t0\t1\tls\n1702400000\t5\t0\t\tls\n";
+        let entries = EventLogParser::new().parse_content(content);
+
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].cwd.is_none());
+    }
+}
@@ -0,0 +1,216 @@
+//! Fish history parser.
+//!
+//! Parses the YAML-like format fish writes to `fish_history`:
+//!
+//! ```text
+//! - cmd: git status
+//!   when: 1702400000
+//! - cmd: vim src/main.rs
+//!   when: 1702400100
+//!   paths:
+//!     - src/main.rs
+//! ```
+//!
+//! Where:
+//! - `cmd` is the command, with backslashes and newlines escaped
+//!   as `\\` and `\n`
+//! - `when` is Unix epoch seconds
+//! - `paths` lists arguments fish recognized as existing paths
+
+use super::{entry::HistoryEntry, HistoryParser};
+use anyhow::{Context, Result};
+use std::path::Path;
+use time::OffsetDateTime;
+
+/// Parser for the fish history format.
+#[derive(Debug, Default)]
+pub struct FishHistoryParser {
+    /// Whether to preserve full command lines (for internal analysis)
+    preserve_full_line: bool,
+}
+
+/// A record being assembled while parsing.
+#[derive(Default)]
+struct PendingRecord {
+    command: String,
+    timestamp: Option<OffsetDateTime>,
+    paths: Vec<String>,
+    line_number: usize,
+}
+
+impl FishHistoryParser {
+    /// Create a new fish history parser.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure whether to preserve full command lines.
+    pub fn preserve_full_lines(mut self, preserve: bool) -> Self {
+        self.preserve_full_line = preserve;
+        self
+    }
+
+    /// Turn a finished record into an entry.
+    fn finish(&self, record: PendingRecord) -> Option<HistoryEntry> {
+        if record.command.trim().is_empty() {
+            return None;
+        }
+
+        let mut entry =
+            HistoryEntry::from_command_line(&record.command, record.timestamp, record.line_number);
+        entry.paths = record.paths;
+
+        if self.preserve_full_line {
+            entry = entry.with_full_line(record.command);
+        }
+
+        Some(entry)
+    }
+
+    /// Parse content record by record.
+    fn parse_content(&self, content: &str) -> Vec<HistoryEntry> {
+        let mut entries = Vec::new();
+        let mut current: Option<PendingRecord> = None;
+        let mut in_paths = false;
+
+        for (index, line) in content.lines().enumerate() {
+            if let Some(cmd) = line.strip_prefix("- cmd:") {
+                if let Some(entry) = current.take().and_then(|r| self.finish(r)) {
+                    entries.push(entry);
+                }
+                current = Some(PendingRecord {
+                    // Keep any leading space after the separator (ignore rules use it)
+                    command: unescape(cmd.strip_prefix(' ').unwrap_or(cmd)),
+                    line_number: index + 1,
+                    ..Default::default()
+                });
+                in_paths = false;
+                continue;
+            }
+
+            let Some(record) = current.as_mut() else {
+                continue;
+            };
+
+            let trimmed = line.trim_start();
+
+            if let Some(when) = trimmed.strip_prefix("when:") {
+                record.timestamp = when
+                    .trim()
+                    .parse::<i64>()
+                    .ok()
+                    .and_then(|ts| OffsetDateTime::from_unix_timestamp(ts).ok());
+                in_paths = false;
+            } else if trimmed.starts_with("paths:") {
+                in_paths = true;
+            } else if let Some(path) = trimmed.strip_prefix("- ") {
+                if in_paths {
+                    record.paths.push(unescape(path));
+                }
+            }
+        }
+
+        if let Some(entry) = current.and_then(|r| self.finish(r)) {
+            entries.push(entry);
+        }
+
+        entries
+    }
+}
+
+impl HistoryParser for FishHistoryParser {
+    fn parse_file(&self, path: &Path) -> Result<Vec<HistoryEntry>> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read history file: {:?}", path))?;
+        let content = String::from_utf8_lossy(&bytes);
+        Ok(self.parse_content(&content))
+    }
+
+    fn parse_str(&self, content: &str) -> Result<Vec<HistoryEntry>> {
+        Ok(self.parse_content(content))
+    }
+}
+
+/// Undo fish's history escaping (`\\` and `\n`).
+///
+/// Unknown escape sequences are kept verbatim.
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('\\') => result.push('\\'),
+            Some('n') => result.push('\n'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_records_with_timestamps() {
+        let parser = FishHistoryParser::new();
+        let content =
+            "- cmd: git status\n  when: 1702400000\n- cmd: cargo build\n  when: 1702400100\n";
+        let entries = parser.parse_content(content);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].command, "git");
+        assert_eq!(entries[1].command, "cargo");
+        assert_eq!(entries[0].timestamp.unwrap().unix_timestamp(), 1702400000);
+        assert_eq!(entries[1].line_number, 3);
+    }
+
+    #[test]
+    fn paths_kept_as_metadata() {
+        let parser = FishHistoryParser::new();
+        let content = "- cmd: vim src/main.rs Cargo.toml\n  when: 1702400000\n  paths:\n    - src/main.rs\n    - Cargo.toml\n- cmd: ls\n  when: 1702400100\n";
+        let entries = parser.parse_content(content);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].paths, vec!["src/main.rs", "Cargo.toml"]);
+        assert!(entries[1].paths.is_empty());
+    }
+
+    #[test]
+    fn escaped_multiline_command() {
+        let parser = FishHistoryParser::new().preserve_full_lines(true);
+        let content = "- cmd: for f in *.rs\\n  wc -l $f\\nend\n  when: 1702400000\n";
+        let entries = parser.parse_content(content);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].commands, ["wc"]);
+        assert_eq!(entries[0].full_line, "for f in *.rs\n  wc -l $f\nend");
+    }
+
+    #[test]
+    fn unescape_handles_backslashes() {
+        assert_eq!(unescape(r"echo a\\b"), r"echo a\b");
+        assert_eq!(unescape(r"printf \\n"), r"printf \n");
+        assert_eq!(unescape(r"echo \t"), r"echo \t");
+    }
+
+    #[test]
+    fn record_without_when_has_no_timestamp() {
+        let parser = FishHistoryParser::new();
+        let entries = parser.parse_content("- cmd: ls\n");
+
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].timestamp.is_none());
+    }
+}
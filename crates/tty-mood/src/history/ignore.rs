@@ -0,0 +1,372 @@
+//! Ignore rules for history analysis.
+//!
+//! Some commands should never reach mood analysis: password managers,
+//! anything touching `~/.ssh`, or lines typed with a leading space
+//! (zsh `HIST_IGNORE_SPACE`, bash `HISTCONTROL=ignorespace`).
+//!
+//! Rules are applied while parsing, before entries are merged, cached
+//! or analyzed, so ignored commands are never persisted anywhere.
+//! The one exception is the event log the `tty-mood init` hooks write
+//! from the shell (see [`super::events`]): the hooks skip lines with a
+//! leading space, but know nothing of these rules and log the first
+//! word of every other command.
+//!
+//! Patterns are globs (`pass*`, `*.ssh/*`) unless prefixed with `re:`,
+//! in which case they are regular expressions (`re:^op\b`). Command
+//! patterns match every command a line invokes; line patterns match
+//! the full command line.
+
+use super::{fingerprint, HistoryEntry, HistoryParser};
+use anyhow::{Context, Result};
+use globset::{Glob, GlobMatcher};
+use regex::Regex;
+use std::cell::RefCell;
+use std::path::Path;
+
+/// A compiled glob or regex pattern.
+#[derive(Debug, Clone)]
+enum Matcher {
+    Glob(GlobMatcher),
+    Regex(Regex),
+}
+
+/// One ignore pattern with its source text.
+#[derive(Debug, Clone)]
+struct Pattern {
+    source: String,
+    matcher: Matcher,
+}
+
+impl Pattern {
+    fn parse(source: &str) -> Result<Self> {
+        let matcher = match source.strip_prefix("re:") {
+            Some(regex) => Matcher::Regex(
+                Regex::new(regex).with_context(|| format!("Invalid ignore regex: {}", source))?,
+            ),
+            None => Matcher::Glob(
+                Glob::new(source)
+                    .with_context(|| format!("Invalid ignore glob: {}", source))?
+                    .compile_matcher(),
+            ),
+        };
+
+        Ok(Self {
+            source: source.to_string(),
+            matcher,
+        })
+    }
+
+    fn is_match(&self, text: &str) -> bool {
+        match &self.matcher {
+            // Globs match the whole text, regexes anywhere in it
+            Matcher::Glob(glob) => glob.is_match(text),
+            Matcher::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+/// Why an entry was ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IgnoreReason {
+    /// The line started with a space
+    LeadingSpace,
+    /// A command pattern matched (index into the command patterns)
+    Command(usize),
+    /// A line pattern matched (index into the line patterns)
+    Line(usize),
+}
+
+/// Compiled ignore rules.
+#[derive(Debug, Clone)]
+pub struct IgnoreRules {
+    leading_space: bool,
+    commands: Vec<Pattern>,
+    lines: Vec<Pattern>,
+}
+
+impl Default for IgnoreRules {
+    fn default() -> Self {
+        Self {
+            leading_space: true,
+            commands: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+}
+
+impl IgnoreRules {
+    /// Compile rules from pattern strings.
+    pub fn new(leading_space: bool, commands: &[String], lines: &[String]) -> Result<Self> {
+        Ok(Self {
+            leading_space,
+            commands: commands
+                .iter()
+                .map(|p| Pattern::parse(p))
+                .collect::<Result<_>>()?,
+            lines: lines
+                .iter()
+                .map(|p| Pattern::parse(p))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Decide whether an entry should be ignored, and why.
+    ///
+    /// Line rules need the full line; entries parsed without it are
+    /// only checked against command rules.
+    pub fn check(&self, entry: &HistoryEntry) -> Option<IgnoreReason> {
+        if self.leading_space && entry.full_line.starts_with(' ') {
+            return Some(IgnoreReason::LeadingSpace);
+        }
+
+        for (index, pattern) in self.commands.iter().enumerate() {
+            let mut names = std::iter::once(&entry.command).chain(&entry.commands);
+            if names.any(|name| pattern.is_match(name)) {
+                return Some(IgnoreReason::Command(index));
+            }
+        }
+
+        if !entry.full_line.is_empty() {
+            for (index, pattern) in self.lines.iter().enumerate() {
+                if pattern.is_match(&entry.full_line) {
+                    return Some(IgnoreReason::Line(index));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Remove ignored entries, counting them in `stats`.
+    pub fn apply(&self, entries: &mut Vec<HistoryEntry>, stats: &mut IgnoreStats) {
+        entries.retain(|entry| match self.check(entry) {
+            Some(reason) => {
+                stats.record(reason);
+                false
+            }
+            None => true,
+        });
+    }
+
+    /// Stable identifier of these rules, so caches built under other
+    /// rules can be discarded.
+    pub fn fingerprint(&self) -> u64 {
+        let mut key = String::from(if self.leading_space { "space" } else { "" });
+        for pattern in &self.commands {
+            key.push_str("\0c:");
+            key.push_str(&pattern.source);
+        }
+        for pattern in &self.lines {
+            key.push_str("\0l:");
+            key.push_str(&pattern.source);
+        }
+        fingerprint(key.as_bytes())
+    }
+
+    /// Describe an ignore reason for humans.
+    pub fn describe(&self, reason: IgnoreReason) -> String {
+        match reason {
+            IgnoreReason::LeadingSpace => "leading space".to_string(),
+            IgnoreReason::Command(index) => format!("command {}", self.commands[index].source),
+            IgnoreReason::Line(index) => format!("line {}", self.lines[index].source),
+        }
+    }
+}
+
+/// How many entries each rule removed.
+#[derive(Debug, Default, Clone)]
+pub struct IgnoreStats {
+    counts: Vec<(IgnoreReason, usize)>,
+}
+
+impl IgnoreStats {
+    fn record(&mut self, reason: IgnoreReason) {
+        self.add(reason, 1);
+    }
+
+    fn add(&mut self, reason: IgnoreReason, n: usize) {
+        match self.counts.iter_mut().find(|(r, _)| *r == reason) {
+            Some((_, count)) => *count += n,
+            None => self.counts.push((reason, n)),
+        }
+    }
+
+    /// Add another set of counts to this one.
+    pub fn merge(&mut self, other: &IgnoreStats) {
+        for &(reason, count) in &other.counts {
+            self.add(reason, count);
+        }
+    }
+
+    /// Total number of ignored entries.
+    pub fn total(&self) -> usize {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Counts per reason, in the order reasons were first seen.
+    pub fn counts(&self) -> &[(IgnoreReason, usize)] {
+        &self.counts
+    }
+}
+
+/// A parser that drops ignored entries from another parser's output.
+pub struct IgnoringParser<'a> {
+    inner: Box<dyn HistoryParser>,
+    rules: &'a IgnoreRules,
+    stats: RefCell<IgnoreStats>,
+}
+
+impl<'a> IgnoringParser<'a> {
+    /// Wrap a parser. It should preserve full lines for line rules to apply.
+    pub fn new(inner: Box<dyn HistoryParser>, rules: &'a IgnoreRules) -> Self {
+        Self {
+            inner,
+            rules,
+            stats: RefCell::new(IgnoreStats::default()),
+        }
+    }
+
+    /// Counts of everything ignored so far.
+    pub fn stats(&self) -> IgnoreStats {
+        self.stats.borrow().clone()
+    }
+
+    fn filter(&self, mut entries: Vec<HistoryEntry>) -> Vec<HistoryEntry> {
+        self.rules.apply(&mut entries, &mut self.stats.borrow_mut());
+        entries
+    }
+}
+
+impl HistoryParser for IgnoringParser<'_> {
+    fn parse_file(&self, path: &Path) -> Result<Vec<HistoryEntry>> {
+        self.inner.parse_file(path).map(|e| self.filter(e))
+    }
+
+    fn parse_str(&self, content: &str) -> Result<Vec<HistoryEntry>> {
+        self.inner.parse_str(content).map(|e| self.filter(e))
+    }
+
+    fn parse_bytes(&self, bytes: &[u8]) -> Result<Vec<HistoryEntry>> {
+        self.inner.parse_bytes(bytes).map(|e| self.filter(e))
+    }
+
+    fn cache_key(&self) -> u64 {
+        self.inner.cache_key() ^ self.rules.fingerprint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::ZshHistoryParser;
+
+    fn rules(commands: &[&str], lines: &[&str]) -> IgnoreRules {
+        let commands: Vec<String> = commands.iter().map(|s| s.to_string()).collect();
+        let lines: Vec<String> = lines.iter().map(|s| s.to_string()).collect();
+        IgnoreRules::new(true, &commands, &lines).unwrap()
+    }
+
+    fn entry(line: &str) -> HistoryEntry {
+        HistoryEntry::from_command_line(line, None, 1).with_full_line(line.to_string())
+    }
+
+    #[test]
+    fn leading_space_is_ignored() {
+        let rules = IgnoreRules::default();
+        assert_eq!(
+            rules.check(&entry(" export TOKEN=abc")),
+            Some(IgnoreReason::LeadingSpace)
+        );
+        assert_eq!(rules.check(&entry("ls")), None);
+
+        let off = IgnoreRules::new(false, &[], &[]).unwrap();
+        assert_eq!(off.check(&entry(" ls")), None);
+    }
+
+    #[test]
+    fn command_globs_match_any_invoked_command() {
+        let rules = rules(&["pass", "gopass*"], &[]);
+
+        assert_eq!(
+            rules.check(&entry("pass show web")),
+            Some(IgnoreReason::Command(0))
+        );
+        assert_eq!(
+            rules.check(&entry("gopass-jsonapi")),
+            Some(IgnoreReason::Command(1))
+        );
+        assert_eq!(
+            rules.check(&entry("echo x | pass insert -m y")),
+            Some(IgnoreReason::Command(0))
+        );
+        // Globs match whole names
+        assert_eq!(rules.check(&entry("passwd")), None);
+    }
+
+    #[test]
+    fn line_patterns_match_full_lines() {
+        let rules = rules(&[], &["*.ssh/*", "re:(?i)password="]);
+
+        assert_eq!(
+            rules.check(&entry("ssh-add ~/.ssh/id_ed25519")),
+            Some(IgnoreReason::Line(0))
+        );
+        assert_eq!(
+            rules.check(&entry("curl -d PASSWORD=hunter2 x")),
+            Some(IgnoreReason::Line(1))
+        );
+        assert_eq!(rules.check(&entry("ssh host")), None);
+    }
+
+    #[test]
+    fn invalid_patterns_are_errors() {
+        assert!(IgnoreRules::new(true, &["[".to_string()], &[]).is_err());
+        assert!(IgnoreRules::new(true, &[], &["re:(".to_string()]).is_err());
+    }
+
+    #[test]
+    fn parser_wrapper_filters_and_counts() {
+        let rules = rules(&["pass"], &["*.ssh/*"]);
+        let parser = IgnoringParser::new(
+            Box::new(ZshHistoryParser::new().preserve_full_lines(true)),
+            &rules,
+        );
+
+        let entries = parser
+            .parse_str(
+                ": 1702400000:0;git status\n\
+                 : 1702400001:0; export TOKEN=abc\n\
+                 : 1702400002:0;pass show web\n\
+                 : 1702400003:0;cat ~/.ssh/config\n\
+                 : 1702400004:0;pass -c mail\n",
+            )
+            .unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].command, "git");
+
+        let stats = parser.stats();
+        assert_eq!(stats.total(), 4);
+        let described: Vec<_> = stats
+            .counts()
+            .iter()
+            .map(|(reason, count)| (rules.describe(*reason), *count))
+            .collect();
+        assert_eq!(
+            described,
+            [
+                ("leading space".to_string(), 1),
+                ("command pass".to_string(), 2),
+                ("line *.ssh/*".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn fingerprint_tracks_rule_changes() {
+        let a = rules(&["pass"], &[]);
+        let b = rules(&["pass", "op"], &[]);
+        assert_ne!(a.fingerprint(), b.fingerprint());
+        assert_eq!(a.fingerprint(), rules(&["pass"], &[]).fingerprint());
+    }
+}
@@ -0,0 +1,782 @@
+//! A small shell lexer for finding the commands a line invokes.
+//!
+//! Not a full shell parser. It understands enough POSIX/zsh syntax
+//! to tell commands from arguments:
+//!
+//! - quoting (`'...'`, `"..."`) and backslash escapes
+//! - command separators: `|`, `|&`, `&&`, `||`, `;`, `&`, newlines
+//! - command substitution: `$( )` and backticks, also inside `"..."`
+//! - subshells `( )`, process substitution `<( )` and `>( )`
+//! - redirections (`>`, `2>&1`, `<<EOF` heredocs)
+//! - environment assignments (`FOO="a b" cmd`) and prefix commands
+//!   (`sudo`, `env`, `time`, ...)
+//! - reserved words (`if`, `then`, `do`, `done`, ...), including
+//!   fish's `begin`, `end`, `and`, `or` and `not`
+//!
+//! Example: `FOO="x y" cat a | grep -c "$(whoami)" && cargo test`
+//! invokes `cat`, `grep`, `whoami` and `cargo`.
+//!
+//! Along the way it counts the line's structure (pipes, redirections,
+//! substitutions, loops) into a [`LineShape`], which holds no text.
+
+use serde::{Deserialize, Serialize};
+
+/// Commands that run another command given as their argument.
+const PREFIX_COMMANDS: &[&str] = &[
+    "sudo", "command", "builtin", "exec", "env", "nice", "nohup", "time", "doas",
+];
+
+/// Reserved words after which a command is expected.
+const OPENING_KEYWORDS: &[&str] = &[
+    "if", "then", "else", "elif", "do", "while", "until", "!", "{", "coproc", "begin", "and", "or",
+    "not",
+];
+
+/// Reserved words that close a compound command.
+const CLOSING_KEYWORDS: &[&str] = &["fi", "done", "esac", "}", "end"];
+
+/// Reserved words whose following words are not commands.
+const CLAUSE_KEYWORDS: &[&str] = &["for", "select", "case", "function", "[[", "foreach"];
+
+/// Reserved words that start a loop.
+const LOOP_KEYWORDS: &[&str] = &["for", "while", "until", "select", "foreach"];
+
+/// Deepest nesting of subshells and substitutions that is lexed;
+/// anything below it is skipped as plain words.
+const MAX_DEPTH: usize = 64;
+
+/// How elaborate a command line is, without any of its text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineShape {
+    /// Length in characters
+    pub length: usize,
+    /// Pipes (`|`, `|&`)
+    pub pipes: usize,
+    /// Redirections, heredocs included
+    pub redirections: usize,
+    /// Command and process substitutions
+    pub substitutions: usize,
+    /// `for`, `while`, `until`, `select` and `foreach` loops
+    pub loops: usize,
+}
+
+impl LineShape {
+    /// Whether the line uses no shell operators at all.
+    pub fn is_plain(&self) -> bool {
+        self.pipes + self.redirections + self.substitutions + self.loops == 0
+    }
+}
+
+/// What a line invokes and how it is built.
+#[derive(Debug, Clone, Default)]
+pub struct Lexed {
+    /// Every invoked command, in order of appearance
+    pub commands: Vec<String>,
+    /// The word after each command, unless it is an option
+    /// (`add` in `git add -p`, nothing in `git -C repo status`)
+    pub arguments: Vec<Option<String>>,
+    /// Structure of the line
+    pub shape: LineShape,
+}
+
+/// Lex a whole line into its invoked commands and shape.
+///
+/// Comment-only and empty lines invoke nothing.
+pub fn lex(line: &str) -> Lexed {
+    lex_at(line, 0)
+}
+
+/// Lex a line found `depth` substitutions deep.
+fn lex_at(line: &str, depth: usize) -> Lexed {
+    let mut lexer = Lexer::new(line);
+    lexer.depth = depth;
+    lexer.sequence(false);
+    lexer.shape.length = line.trim().chars().count();
+    Lexed {
+        commands: lexer.commands,
+        arguments: lexer.arguments,
+        shape: lexer.shape,
+    }
+}
+
+/// Split the first simple command of a line into words.
+///
+/// Quotes and escapes are removed; splitting stops at the first
+/// operator or comment. `alias ll='ls -l'` gives `["alias", "ll=ls -l"]`.
+pub fn split_words(line: &str) -> Vec<String> {
+    let mut lexer = Lexer::new(line);
+    let mut words = Vec::new();
+
+    loop {
+        while matches!(lexer.peek(), Some(' ' | '\t' | '\r')) {
+            lexer.pos += 1;
+        }
+        match lexer.peek() {
+            None | Some('\n' | ';' | '&' | '|' | '<' | '>' | '(' | ')' | '#') => break,
+            Some(_) => words.push(lexer.word().text),
+        }
+    }
+
+    words
+}
+
+/// What the next word of a simple command is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// The command name (or an assignment, prefix or keyword before it)
+    Command,
+    /// Options of a prefix command, then the command name
+    AfterPrefix,
+    /// An argument
+    Arguments,
+    /// Part of a clause that runs no command (`for x in ...`)
+    Clause,
+}
+
+/// A lexed word with quotes and escapes removed.
+struct Word {
+    text: String,
+    /// Starts with an unquoted `NAME=`
+    assignment: bool,
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    commands: Vec<String>,
+    /// The word after each command, if not an option
+    arguments: Vec<Option<String>>,
+    /// Command whose first argument comes next
+    awaiting_argument: Option<usize>,
+    /// Heredoc delimiters waiting for the end of the current line
+    heredocs: Vec<(String, bool)>,
+    shape: LineShape,
+    /// Subshells and substitutions currently open
+    depth: usize,
+}
+
+impl Lexer {
+    fn new(line: &str) -> Self {
+        Self {
+            chars: line.chars().collect(),
+            pos: 0,
+            commands: Vec::new(),
+            arguments: Vec::new(),
+            awaiting_argument: None,
+            heredocs: Vec::new(),
+            shape: LineShape::default(),
+            depth: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    /// Lex a list of commands, up to a closing `)` if `nested`.
+    fn sequence(&mut self, nested: bool) {
+        let mut state = State::Command;
+        // A prefix command with nothing after it still counts
+        let mut pending_prefix: Option<String> = None;
+
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' => self.pos += 1,
+                ')' => {
+                    self.pos += 1;
+                    if nested {
+                        break;
+                    }
+                    // Stray `)`: ends a `case` pattern, a command follows
+                    state = State::Command;
+                }
+                ';' if self.peek_at(1) == Some(';') => {
+                    // End of a `case` branch, a pattern follows
+                    self.pos += 2;
+                    self.flush_prefix(&mut pending_prefix);
+                    state = State::Clause;
+                }
+                '\n' | ';' | '|' => {
+                    // `|` and `|&`, but not `||`
+                    if c == '|'
+                        && self.peek_at(1) != Some('|')
+                        && (self.pos == 0 || self.chars[self.pos - 1] != '|')
+                    {
+                        self.shape.pipes += 1;
+                    }
+                    self.pos += 1;
+                    if c == '\n' {
+                        self.skip_heredocs();
+                    }
+                    self.flush_prefix(&mut pending_prefix);
+                    state = State::Command;
+                }
+                '&' if self.peek_at(1) == Some('>') => self.redirection(),
+                '&' => {
+                    self.pos += 1;
+                    self.flush_prefix(&mut pending_prefix);
+                    state = State::Command;
+                }
+                '<' | '>' => self.redirection(),
+                '(' if self.peek_at(1) == Some('(') => self.skip_arithmetic(),
+                '(' if self.peek_at(1) == Some(')') => {
+                    // Function definition `name() { ... }`: the name is no command
+                    self.pos += 2;
+                    if state == State::Arguments {
+                        self.commands.pop();
+                        self.arguments.pop();
+                        self.awaiting_argument = None;
+                    }
+                    state = State::Command;
+                }
+                '(' => {
+                    self.pos += 1;
+                    if matches!(state, State::Command | State::AfterPrefix) {
+                        self.nested();
+                    } else {
+                        // zsh glob qualifiers (`*(.)`) or array literals
+                        self.skip_until(')');
+                    }
+                    state = State::Arguments;
+                }
+                '#' => self.skip_comment(),
+                _ => {
+                    let word = self.word();
+
+                    // File descriptor of a redirection (`2>&1`)
+                    if matches!(self.peek(), Some('<' | '>'))
+                        && !word.text.is_empty()
+                        && word.text.chars().all(|c| c.is_ascii_digit())
+                    {
+                        continue;
+                    }
+
+                    state = self.classify(word, state, &mut pending_prefix);
+                }
+            }
+        }
+
+        self.flush_prefix(&mut pending_prefix);
+    }
+
+    /// Lex a nested list of commands after its opening `(`.
+    fn nested(&mut self) {
+        if self.depth >= MAX_DEPTH {
+            self.skip_until(')');
+            return;
+        }
+        // The command around the substitution still awaits its argument
+        let awaiting = self.awaiting_argument.take();
+        self.depth += 1;
+        self.sequence(true);
+        self.depth -= 1;
+        self.awaiting_argument = awaiting;
+    }
+
+    /// Decide what a word is, record it if it's a command, return the next state.
+    fn classify(&mut self, word: Word, state: State, pending_prefix: &mut Option<String>) -> State {
+        let text = word.text;
+
+        match state {
+            State::Arguments => {
+                if let Some(command) = self.awaiting_argument.take() {
+                    if !text.is_empty() && !text.starts_with('-') {
+                        self.arguments[command] = Some(text);
+                    }
+                }
+                state
+            }
+            State::Clause => state,
+            State::AfterPrefix if text.starts_with('-') => State::AfterPrefix,
+            State::Command | State::AfterPrefix => {
+                self.awaiting_argument = None;
+                if word.assignment {
+                    return state;
+                }
+                if text.is_empty() {
+                    return State::Arguments;
+                }
+                if LOOP_KEYWORDS.contains(&text.as_str()) {
+                    self.shape.loops += 1;
+                }
+                if PREFIX_COMMANDS.contains(&text.as_str()) {
+                    pending_prefix.get_or_insert(text);
+                    return State::AfterPrefix;
+                }
+                if OPENING_KEYWORDS.contains(&text.as_str()) {
+                    return State::Command;
+                }
+                if CLOSING_KEYWORDS.contains(&text.as_str()) {
+                    return State::Arguments;
+                }
+                if CLAUSE_KEYWORDS.contains(&text.as_str()) {
+                    return State::Clause;
+                }
+
+                *pending_prefix = None;
+                self.push_command(text);
+                State::Arguments
+            }
+        }
+    }
+
+    /// Record an invoked command, its argument still to come.
+    fn push_command(&mut self, command: String) {
+        self.awaiting_argument = Some(self.commands.len());
+        self.commands.push(command);
+        self.arguments.push(None);
+    }
+
+    /// Record a prefix command that was not followed by a command.
+    fn flush_prefix(&mut self, pending_prefix: &mut Option<String>) {
+        if let Some(prefix) = pending_prefix.take() {
+            self.push_command(prefix);
+            self.awaiting_argument = None;
+        }
+    }
+
+    /// Lex one word, recursing into command substitutions.
+    fn word(&mut self) -> Word {
+        let mut text = String::new();
+        let mut assignment = false;
+        // Whether everything so far could be a variable name
+        let mut name = true;
+
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' | '\n' | ';' | '&' | '|' | '<' | '>' | '(' | ')' => break,
+                '\\' => {
+                    self.pos += 1;
+                    if let Some(escaped) = self.peek() {
+                        self.pos += 1;
+                        if escaped != '\n' {
+                            text.push(escaped);
+                        }
+                    }
+                    name = false;
+                }
+                '\'' => {
+                    self.pos += 1;
+                    while let Some(quoted) = self.peek() {
+                        self.pos += 1;
+                        if quoted == '\'' {
+                            break;
+                        }
+                        text.push(quoted);
+                    }
+                    name = false;
+                }
+                '"' => {
+                    self.pos += 1;
+                    self.double_quoted(&mut text);
+                    name = false;
+                }
+                '$' => {
+                    self.dollar(&mut text);
+                    name = false;
+                }
+                '`' => {
+                    self.backticks();
+                    name = false;
+                }
+                '=' if name && !assignment && !text.is_empty() => {
+                    self.pos += 1;
+                    assignment = !text.starts_with(|c: char| c.is_ascii_digit());
+                    text.push('=');
+                    name = false;
+                }
+                _ => {
+                    self.pos += 1;
+                    name &= c.is_alphanumeric() || c == '_';
+                    text.push(c);
+                }
+            }
+        }
+
+        Word { text, assignment }
+    }
+
+    /// Lex the inside of `"..."` after the opening quote.
+    fn double_quoted(&mut self, text: &mut String) {
+        while let Some(c) = self.peek() {
+            match c {
+                '"' => {
+                    self.pos += 1;
+                    return;
+                }
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(escaped @ ('$' | '`' | '"' | '\\')) => {
+                            self.pos += 1;
+                            text.push(escaped);
+                        }
+                        Some('\n') => self.pos += 1,
+                        _ => text.push('\\'),
+                    }
+                }
+                '$' => self.dollar(text),
+                '`' => self.backticks(),
+                _ => {
+                    self.pos += 1;
+                    text.push(c);
+                }
+            }
+        }
+    }
+
+    /// Lex a `$` expansion: `$( )`, `$(( ))`, `${ }` or a plain `$`.
+    fn dollar(&mut self, text: &mut String) {
+        self.pos += 1;
+        text.push('$');
+
+        match self.peek() {
+            Some('(') if self.peek_at(1) == Some('(') => self.skip_arithmetic(),
+            Some('(') => {
+                self.pos += 1;
+                self.shape.substitutions += 1;
+                self.nested();
+            }
+            Some('{') => {
+                self.pos += 1;
+                self.skip_until('}');
+            }
+            _ => {}
+        }
+    }
+
+    /// Lex a backtick command substitution after the opening backtick.
+    fn backticks(&mut self) {
+        self.pos += 1;
+        let mut inner = String::new();
+
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '`' => break,
+                '\\' => {
+                    if let Some(escaped) = self.peek() {
+                        self.pos += 1;
+                        inner.push(escaped);
+                    }
+                }
+                _ => inner.push(c),
+            }
+        }
+
+        if self.depth >= MAX_DEPTH {
+            self.shape.substitutions += 1;
+            return;
+        }
+        let Lexed {
+            commands,
+            arguments,
+            shape,
+        } = lex_at(&inner, self.depth + 1);
+        self.commands.extend(commands);
+        self.arguments.extend(arguments);
+        self.shape.pipes += shape.pipes;
+        self.shape.redirections += shape.redirections;
+        self.shape.substitutions += shape.substitutions + 1;
+        self.shape.loops += shape.loops;
+    }
+
+    /// Lex a redirection operator and its target.
+    fn redirection(&mut self) {
+        let mut op = String::new();
+        while let Some(c @ ('<' | '>' | '&' | '|')) = self.peek() {
+            // `>&` and `<&` take a descriptor, but `> x && y` is two operators
+            if c == '&' && op.ends_with('&') || c == '|' && !op.ends_with('>') {
+                break;
+            }
+            op.push(c);
+            self.pos += 1;
+        }
+
+        // Process substitution: `<(cmd)` and `>(cmd)`
+        if (op == "<" || op == ">") && self.peek() == Some('(') {
+            self.pos += 1;
+            self.shape.substitutions += 1;
+            self.nested();
+            return;
+        }
+
+        self.shape.redirections += 1;
+
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+
+        let target = self.word();
+
+        if op.starts_with("<<") && op != "<<<" {
+            let delimiter = target.text.trim_start_matches('-').to_string();
+            let strip_tabs = op == "<<-" || target.text.starts_with('-');
+            if !delimiter.is_empty() {
+                self.heredocs.push((delimiter, strip_tabs));
+            }
+        }
+    }
+
+    /// Skip heredoc bodies that start after the current newline.
+    fn skip_heredocs(&mut self) {
+        for (delimiter, strip_tabs) in std::mem::take(&mut self.heredocs) {
+            while self.pos < self.chars.len() {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+                let line: String = self.chars[start..self.pos].iter().collect();
+                self.pos += 1;
+
+                let line = if strip_tabs {
+                    line.trim_start_matches('\t')
+                } else {
+                    &line
+                };
+                if line == delimiter {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Skip `(( ... ))` arithmetic, which invokes no commands.
+    fn skip_arithmetic(&mut self) {
+        self.pos += 2;
+        let mut depth = 2;
+
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Skip to just past a closing character, respecting nesting.
+    fn skip_until(&mut self, close: char) {
+        let open = if close == ')' { '(' } else { '{' };
+        let mut depth = 1;
+
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                depth -= 1;
+                if depth == 0 {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Skip a comment up to (not including) the end of the line.
+    fn skip_comment(&mut self) {
+        while self.peek().is_some_and(|c| c != '\n') {
+            self.pos += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commands(line: &str) -> Vec<String> {
+        lex(line).commands
+    }
+
+    fn line_shape(line: &str) -> LineShape {
+        lex(line).shape
+    }
+
+    #[test]
+    fn simple_command() {
+        assert_eq!(commands("git status"), ["git"]);
+        assert_eq!(commands("  ls -la  "), ["ls"]);
+        assert!(commands("").is_empty());
+    }
+
+    #[test]
+    fn pipelines_and_lists() {
+        assert_eq!(
+            commands("cat x | grep y && cargo test"),
+            ["cat", "grep", "cargo"]
+        );
+        assert_eq!(commands("make || echo failed; ls"), ["make", "echo", "ls"]);
+        assert_eq!(commands("sleep 10 & htop"), ["sleep", "htop"]);
+        assert_eq!(commands("make 2>&1 |& tee log"), ["make", "tee"]);
+    }
+
+    #[test]
+    fn quotes_and_escapes_hide_operators() {
+        assert_eq!(commands("echo 'a | b' \"c && d\" e\\;f"), ["echo"]);
+        assert_eq!(commands("grep \"it's\" file"), ["grep"]);
+    }
+
+    #[test]
+    fn quoted_env_assignments() {
+        assert_eq!(commands("FOO=bar mycommand"), ["mycommand"]);
+        assert_eq!(commands("A=1 B=2 gcc main.c"), ["gcc"]);
+        assert_eq!(commands("FOO=\"a b\" RUST_LOG='x y' cargo run"), ["cargo"]);
+        assert_eq!(commands("x=$(date)"), ["date"]);
+    }
+
+    #[test]
+    fn prefix_commands() {
+        assert_eq!(commands("sudo pacman -S vim"), ["pacman"]);
+        assert_eq!(commands("sudo -E env FOO=1 make"), ["make"]);
+        assert_eq!(commands("sudo"), ["sudo"]);
+        assert_eq!(commands("time cargo build"), ["cargo"]);
+    }
+
+    #[test]
+    fn arguments_follow_their_commands() {
+        let lexed = lex("sudo git push && FOO=1 cargo -q test | grep x; ls");
+        assert_eq!(lexed.commands, ["git", "cargo", "grep", "ls"]);
+        assert_eq!(
+            lexed.arguments,
+            [Some("push".to_string()), None, Some("x".to_string()), None]
+        );
+
+        let lexed = lex("git commit -m \"$(date) done\"");
+        assert_eq!(lexed.commands, ["git", "date"]);
+        assert_eq!(lexed.arguments, [Some("commit".to_string()), None]);
+
+        let lexed = lex("mkcd() { mkdir x; }");
+        assert_eq!(lexed.commands, ["mkdir"]);
+        assert_eq!(lexed.arguments.len(), 1);
+    }
+
+    #[test]
+    fn line_shape_counts_structure() {
+        let shape = line_shape("for f in *.log; do grep -c x \"$f\" | sort > out 2>&1; done");
+        assert_eq!(shape.loops, 1);
+        assert_eq!(shape.pipes, 1);
+        assert_eq!(shape.redirections, 2);
+        assert_eq!(shape.substitutions, 0);
+
+        let shape = line_shape("diff <(ls a) <(ls b) || echo `date | tr a b` $(id -u)");
+        assert_eq!(shape.pipes, 1);
+        assert_eq!(shape.substitutions, 4);
+        assert_eq!(shape.redirections, 0);
+
+        let plain = line_shape("  git status  ");
+        assert!(plain.is_plain());
+        assert_eq!(plain.length, 10);
+        assert!(line_shape("echo 'a | b > c'").is_plain());
+    }
+
+    #[test]
+    fn command_substitution() {
+        assert_eq!(
+            commands("echo $(date) `whoami`"),
+            ["echo", "date", "whoami"]
+        );
+        assert_eq!(
+            commands("kill \"$(pgrep -f 'my app' | head -1)\""),
+            ["kill", "pgrep", "head"]
+        );
+        assert_eq!(commands("echo $((1 + 2))"), ["echo"]);
+        assert_eq!(commands("echo ${HOME:-a b}"), ["echo"]);
+    }
+
+    #[test]
+    fn subshells_and_process_substitution() {
+        assert_eq!(commands("(cd src && make) > log"), ["cd", "make"]);
+        assert_eq!(
+            commands("diff <(sort a) <(sort b)"),
+            ["diff", "sort", "sort"]
+        );
+    }
+
+    #[test]
+    fn compound_commands() {
+        assert_eq!(commands("for f in *.rs; do rustfmt $f; done"), ["rustfmt"]);
+        assert_eq!(
+            commands("if [ -f x ]; then cat x; else touch x; fi"),
+            ["[", "cat", "touch"]
+        );
+        assert_eq!(
+            commands("while true; do curl localhost; sleep 1; done"),
+            ["true", "curl", "sleep"]
+        );
+        assert_eq!(
+            commands("case $x in a) echo a;; *) ls;; esac"),
+            ["echo", "ls"]
+        );
+    }
+
+    #[test]
+    fn function_definitions() {
+        assert_eq!(
+            commands("mkcd() { mkdir -p $1 && cd $1; }"),
+            ["mkdir", "cd"]
+        );
+    }
+
+    #[test]
+    fn fish_syntax() {
+        assert_eq!(
+            commands("for f in *.rs\n  wc -l $f\nend; and echo ok"),
+            ["wc", "echo"]
+        );
+    }
+
+    #[test]
+    fn heredocs_are_not_commands() {
+        assert_eq!(
+            commands("cat <<EOF > out\nrm -rf /\nEOF\nls"),
+            ["cat", "ls"]
+        );
+    }
+
+    #[test]
+    fn comments_invoke_nothing() {
+        assert!(commands("# just a note").is_empty());
+        assert_eq!(commands("ls # list"), ["ls"]);
+        assert_eq!(
+            commands("# install fonts\nsudo pacman -S ttf\nfc-cache -fv"),
+            ["pacman", "fc-cache"]
+        );
+    }
+
+    #[test]
+    fn deep_nesting_stops_at_the_limit() {
+        let depth = 100_000;
+        let line = format!("{}ls{}", "echo $(".repeat(depth), ")".repeat(depth));
+        let found = commands(&line);
+        assert_eq!(found.len(), MAX_DEPTH + 1);
+        assert!(found.iter().all(|c| c == "echo"));
+
+        let line = format!("{}ls{}", "( ".repeat(depth), ")".repeat(depth));
+        assert!(commands(&line).is_empty());
+    }
+
+    #[test]
+    fn split_words_removes_quotes() {
+        assert_eq!(
+            split_words("alias gst='git status' k=kubectl"),
+            ["alias", "gst=git status", "k=kubectl"]
+        );
+        assert_eq!(split_words("echo \"a b\" c\\ d; ls"), ["echo", "a b", "c d"]);
+    }
+
+    #[test]
+    fn non_ascii_commands() {
+        assert_eq!(commands("./größe.sh && 日本 x"), ["./größe.sh", "日本"]);
+    }
+}
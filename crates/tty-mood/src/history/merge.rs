@@ -0,0 +1,102 @@
+//! Merging history from several sources.
+//!
+//! Entries from all sources are combined into one timeline. Duplicates
+//! across sources are dropped: zsh with `SHARE_HISTORY` (or a history
+//! imported into Atuin) records the same command with the same timestamp
+//! more than once. Within one source a repeat is a real repeat: history
+//! with second precision easily holds `ls` twice in the same second.
+
+use super::HistoryEntry;
+use std::collections::HashMap;
+
+/// Merge entries from several sources in timestamp order.
+///
+/// Each entry's `source` is set to the index of the list it came from.
+/// A timed entry is a duplicate when another source recorded the same
+/// line at the same time first. Entries without timestamps sort first
+/// and are never deduplicated, since there is no way to tell a repeat
+/// from a duplicate.
+pub fn merge(sources: Vec<Vec<HistoryEntry>>) -> Vec<HistoryEntry> {
+    let mut merged: Vec<HistoryEntry> = sources
+        .into_iter()
+        .enumerate()
+        .flat_map(|(index, entries)| {
+            entries.into_iter().map(move |mut entry| {
+                entry.source = index;
+                entry
+            })
+        })
+        .collect();
+
+    // Stable sort keeps the original order within equal timestamps
+    merged.sort_by_key(|e| e.timestamp);
+
+    // Which source recorded each line first
+    let mut first_source = HashMap::new();
+    merged.retain(|entry| match entry.timestamp {
+        Some(ts) => {
+            let key = (ts, entry.command.clone(), entry.line_hash);
+            *first_source.entry(key).or_insert(entry.source) == entry.source
+        }
+        None => true,
+    });
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::OffsetDateTime;
+
+    fn entry_at(line: &str, ts: i64) -> HistoryEntry {
+        let command = line.split_whitespace().next().unwrap().to_string();
+        HistoryEntry::new(command, OffsetDateTime::from_unix_timestamp(ts).ok(), 1)
+            .with_full_line(line.to_string())
+    }
+
+    #[test]
+    fn merges_in_timestamp_order() {
+        let zsh = vec![entry_at("git status", 100), entry_at("cargo test", 300)];
+        let bash = vec![entry_at("apt update", 200)];
+
+        let merged = merge(vec![zsh, bash]);
+
+        let commands: Vec<_> = merged.iter().map(|e| e.command.as_str()).collect();
+        assert_eq!(commands, ["git", "apt", "cargo"]);
+        assert_eq!(merged[1].source, 1);
+        assert_eq!(merged[2].source, 0);
+    }
+
+    #[test]
+    fn drops_shared_history_duplicates() {
+        let first = vec![entry_at("git status", 100), entry_at("git push", 100)];
+        let second = vec![entry_at("git status", 100)];
+
+        let merged = merge(vec![first, second]);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].source, 0);
+    }
+
+    #[test]
+    fn keeps_repeats_within_one_source() {
+        let zsh = vec![entry_at("ls", 100), entry_at("ls", 100)];
+        let shared = vec![entry_at("ls", 100)];
+
+        let merged = merge(vec![zsh, shared]);
+
+        assert_eq!(merged.len(), 2);
+        assert!(merged.iter().all(|e| e.source == 0));
+    }
+
+    #[test]
+    fn keeps_untimestamped_repeats() {
+        let entries = vec![
+            HistoryEntry::new("ls".into(), None, 1),
+            HistoryEntry::new("ls".into(), None, 2),
+        ];
+
+        assert_eq!(merge(vec![entries]).len(), 2);
+    }
+}
@@ -3,13 +3,41 @@
 //! This module provides parsers for different shell history formats:
 //! - ZSH (extended format with timestamps)
 //! - Bash (simple format, timestamps via HISTTIMEFORMAT)
+//! - Fish (YAML-like records with `when` and `paths`)
+//! - Atuin (SQLite database with exit status, duration and cwd)
+//! - Nushell (plaintext `history.txt` or `history.sqlite3`)
+//! - PowerShell (PSReadLine `ConsoleHost_history.txt`, no timestamps)
+//! - asciinema recordings (asciicast v2 `.cast` files)
 //!
 //! All parsing is read-only. We never modify history files.
 
+pub mod aliases;
+pub mod asciicast;
+pub mod atuin;
+pub mod bash;
+pub mod cursor;
+pub mod detect;
+pub mod dictionary;
 pub mod entry;
+pub mod events;
+pub mod fish;
+pub mod ignore;
+pub mod lexer;
+pub mod merge;
+pub mod nushell;
+pub mod powershell;
+pub mod projects;
+pub mod session;
 pub mod zsh;
 
+pub use asciicast::AsciicastParser;
+pub use atuin::AtuinHistoryParser;
+pub use bash::BashHistoryParser;
 pub use entry::HistoryEntry;
+pub use events::EventLogParser;
+pub use fish::FishHistoryParser;
+pub use nushell::NushellHistoryParser;
+pub use powershell::PowerShellHistoryParser;
 pub use zsh::ZshHistoryParser;
 
 use anyhow::Result;
@@ -21,9 +49,166 @@ pub trait HistoryParser {
     /// Parse history from a file path.
     fn parse_file(&self, path: &Path) -> Result<Vec<HistoryEntry>>;
 
-    /// Parse history from a string (for testing).
-    #[allow(dead_code)]
+    /// Parse history from a string.
     fn parse_str(&self, content: &str) -> Result<Vec<HistoryEntry>>;
+
+    /// Parse history from raw bytes (e.g. the unread tail of a file).
+    fn parse_bytes(&self, bytes: &[u8]) -> Result<Vec<HistoryEntry>> {
+        self.parse_str(&String::from_utf8_lossy(bytes))
+    }
+
+    /// Identifies settings that change which entries are produced.
+    ///
+    /// Cached parse results are discarded when it changes.
+    fn cache_key(&self) -> u64 {
+        0
+    }
+}
+
+/// Supported shell history formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryFormat {
+    Zsh,
+    Bash,
+    Fish,
+    Atuin,
+    Nushell,
+    PowerShell,
+    Asciicast,
+    Events,
+}
+
+impl HistoryFormat {
+    /// Guess the format from the history file path.
+    ///
+    /// Falls back to zsh, whose parser also accepts plain one-command-per-line files.
+    pub fn from_path(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        let full_path = path.to_string_lossy().to_lowercase();
+
+        if name == "events.log" {
+            HistoryFormat::Events
+        } else if name.ends_with(".cast") {
+            HistoryFormat::Asciicast
+        } else if full_path.contains("atuin") {
+            HistoryFormat::Atuin
+        } else if full_path.contains("nushell") || name == "history.sqlite3" {
+            HistoryFormat::Nushell
+        } else if full_path.contains("psreadline") || name == "consolehost_history.txt" {
+            HistoryFormat::PowerShell
+        } else if name.contains("bash") {
+            HistoryFormat::Bash
+        } else if name.contains("fish") {
+            HistoryFormat::Fish
+        } else {
+            HistoryFormat::Zsh
+        }
+    }
+
+    /// Format name as used on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            HistoryFormat::Zsh => "zsh",
+            HistoryFormat::Bash => "bash",
+            HistoryFormat::Fish => "fish",
+            HistoryFormat::Atuin => "atuin",
+            HistoryFormat::Nushell => "nushell",
+            HistoryFormat::PowerShell => "powershell",
+            HistoryFormat::Asciicast => "asciicast",
+            HistoryFormat::Events => "events",
+        }
+    }
+
+    /// Shell name for this format.
+    pub fn shell(self) -> &'static str {
+        match self {
+            HistoryFormat::Zsh => "zsh",
+            HistoryFormat::Bash => "bash",
+            HistoryFormat::Fish => "fish",
+            HistoryFormat::Atuin => "atuin",
+            HistoryFormat::Nushell => "nu",
+            HistoryFormat::PowerShell => "pwsh",
+            HistoryFormat::Asciicast => "asciinema",
+            HistoryFormat::Events => "hook",
+        }
+    }
+
+    /// Whether files in this format only ever grow by appending,
+    /// so they can be read incrementally from a saved offset.
+    pub fn supports_incremental(self) -> bool {
+        matches!(
+            self,
+            HistoryFormat::Zsh | HistoryFormat::Bash | HistoryFormat::Fish | HistoryFormat::Events
+        )
+    }
+
+    /// Create the parser for this format.
+    ///
+    /// Full command lines are only ever kept in memory, for internal
+    /// analysis such as deduplicating merged sources.
+    pub fn parser(self, preserve_full_lines: bool) -> Box<dyn HistoryParser> {
+        match self {
+            HistoryFormat::Zsh => {
+                Box::new(ZshHistoryParser::new().preserve_full_lines(preserve_full_lines))
+            }
+            HistoryFormat::Bash => {
+                Box::new(BashHistoryParser::new().preserve_full_lines(preserve_full_lines))
+            }
+            HistoryFormat::Fish => {
+                Box::new(FishHistoryParser::new().preserve_full_lines(preserve_full_lines))
+            }
+            HistoryFormat::Atuin => {
+                Box::new(AtuinHistoryParser::new().preserve_full_lines(preserve_full_lines))
+            }
+            HistoryFormat::Nushell => {
+                Box::new(NushellHistoryParser::new().preserve_full_lines(preserve_full_lines))
+            }
+            HistoryFormat::PowerShell => {
+                Box::new(PowerShellHistoryParser::new().preserve_full_lines(preserve_full_lines))
+            }
+            HistoryFormat::Asciicast => {
+                Box::new(AsciicastParser::new().preserve_full_lines(preserve_full_lines))
+            }
+            HistoryFormat::Events => {
+                Box::new(EventLogParser::new().preserve_full_lines(preserve_full_lines))
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for HistoryFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "zsh" => Ok(HistoryFormat::Zsh),
+            "bash" => Ok(HistoryFormat::Bash),
+            "fish" => Ok(HistoryFormat::Fish),
+            "atuin" => Ok(HistoryFormat::Atuin),
+            "nu" | "nushell" => Ok(HistoryFormat::Nushell),
+            "pwsh" | "powershell" => Ok(HistoryFormat::PowerShell),
+            "asciicast" | "asciinema" | "cast" => Ok(HistoryFormat::Asciicast),
+            "events" | "hook" => Ok(HistoryFormat::Events),
+            _ => Err(format!(
+                "Unknown history format: {}. Use 'zsh', 'bash', 'fish', 'atuin', 'nushell', 'powershell', 'asciicast' or 'events'.",
+                s
+            )),
+        }
+    }
+}
+
+/// Stable 64-bit FNV-1a hash.
+///
+/// Unlike `DefaultHasher`, the result never changes between builds,
+/// so it is safe to persist.
+pub fn fingerprint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
 }
 
 /// Filter entries by time range.
@@ -46,18 +231,78 @@ pub fn filter_by_range(
         .collect()
 }
 
-/// Calculate time range for "last N days".
-pub fn last_n_days(days: u32) -> (OffsetDateTime, OffsetDateTime) {
-    let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
-    let duration = time::Duration::days(days as i64);
-    let since = now - duration;
-    (since, now)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn format_from_path() {
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("/home/user/.bash_history")),
+            HistoryFormat::Bash
+        );
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("/home/user/.zsh_history")),
+            HistoryFormat::Zsh
+        );
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("/home/user/.local/share/fish/fish_history")),
+            HistoryFormat::Fish
+        );
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("/home/user/.local/share/atuin/history.db")),
+            HistoryFormat::Atuin
+        );
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("/home/user/.config/nushell/history.txt")),
+            HistoryFormat::Nushell
+        );
+        assert_eq!(
+            HistoryFormat::from_path(Path::new(
+                "/home/user/.local/share/powershell/PSReadLine/ConsoleHost_history.txt"
+            )),
+            HistoryFormat::PowerShell
+        );
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("/tmp/ConsoleHost_history.txt")),
+            HistoryFormat::PowerShell
+        );
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("/backup/bash_history.txt")),
+            HistoryFormat::Bash
+        );
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("/backup/zsh_history.txt")),
+            HistoryFormat::Zsh
+        );
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("/tmp/incident-42.cast")),
+            HistoryFormat::Asciicast
+        );
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("/home/user/.local/share/absurdtty/events.log")),
+            HistoryFormat::Events
+        );
+        assert_eq!(HistoryFormat::from_path(Path::new("history.txt")), HistoryFormat::Zsh);
+    }
+
+    #[test]
+    fn format_from_str() {
+        assert_eq!("zsh".parse::<HistoryFormat>().unwrap(), HistoryFormat::Zsh);
+        assert_eq!("Bash".parse::<HistoryFormat>().unwrap(), HistoryFormat::Bash);
+        assert_eq!("nu".parse::<HistoryFormat>().unwrap(), HistoryFormat::Nushell);
+        assert_eq!("pwsh".parse::<HistoryFormat>().unwrap(), HistoryFormat::PowerShell);
+        assert_eq!("hook".parse::<HistoryFormat>().unwrap(), HistoryFormat::Events);
+        assert!("tcsh".parse::<HistoryFormat>().is_err());
+    }
+
+    #[test]
+    fn fingerprint_is_stable() {
+        assert_eq!(fingerprint(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fingerprint(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(fingerprint(b"git status"), fingerprint(b"git push"));
+    }
+
     #[test]
     fn filter_keeps_entries_without_timestamps() {
         let entries = vec![
@@ -0,0 +1,255 @@
+//! Nushell history parser.
+//!
+//! Nushell stores history in one of two formats, depending on
+//! `$env.config.history.file_format`:
+//!
+//! - `plaintext`: `history.txt`, one command per line, with embedded
+//!   newlines written as the literal sequence `<\n>`
+//! - `sqlite`: `history.sqlite3`, one row per command:
+//!
+//! ```text
+//! history(id, command_line, start_timestamp, session_id, hostname,
+//!         cwd, duration_ms, exit_status, more_info)
+//! ```
+//!
+//! Where `start_timestamp` is Unix epoch milliseconds. The plaintext
+//! format carries no timestamps.
+//!
+//! The SQLite database is opened read-only. We never modify it.
+
+use super::{detect::SQLITE_HEADER, entry::HistoryEntry, HistoryParser};
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OpenFlags};
+use std::io::Read;
+use std::path::Path;
+use time::{Duration, OffsetDateTime};
+
+/// Escape nushell uses for newlines in `history.txt`.
+const NEWLINE_ESCAPE: &str = "<\\n>";
+
+/// Parser for both nushell history formats.
+#[derive(Debug, Default)]
+pub struct NushellHistoryParser {
+    /// Whether to preserve full command lines (for internal analysis)
+    preserve_full_line: bool,
+}
+
+/// A raw row from the `history` table.
+struct NushellRow {
+    command_line: String,
+    start_timestamp_ms: Option<i64>,
+    session_id: Option<i64>,
+    hostname: Option<String>,
+    cwd: Option<String>,
+    duration_ms: Option<i64>,
+    exit_status: Option<i64>,
+}
+
+impl NushellHistoryParser {
+    /// Create a new nushell history parser.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure whether to preserve full command lines.
+    pub fn preserve_full_lines(mut self, preserve: bool) -> Self {
+        self.preserve_full_line = preserve;
+        self
+    }
+
+    /// Build an entry from a complete command line.
+    fn make_entry(
+        &self,
+        command_line: String,
+        timestamp: Option<OffsetDateTime>,
+        line_number: usize,
+    ) -> Option<HistoryEntry> {
+        if command_line.trim().is_empty() {
+            return None;
+        }
+
+        let mut entry = HistoryEntry::from_command_line(&command_line, timestamp, line_number);
+
+        if self.preserve_full_line {
+            entry = entry.with_full_line(command_line);
+        }
+
+        Some(entry)
+    }
+
+    /// Parse the plaintext `history.txt` format.
+    fn parse_content(&self, content: &str) -> Vec<HistoryEntry> {
+        content
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                self.make_entry(line.replace(NEWLINE_ESCAPE, "\n"), None, index + 1)
+            })
+            .collect()
+    }
+
+    /// Read all rows from a `history.sqlite3` connection, oldest first.
+    fn read_rows(&self, conn: &Connection) -> Result<Vec<HistoryEntry>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT command_line, start_timestamp, session_id, hostname,
+                        cwd, duration_ms, exit_status
+                 FROM history
+                 ORDER BY start_timestamp, id",
+            )
+            .context("Failed to query nushell history table")?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(NushellRow {
+                command_line: row.get(0)?,
+                start_timestamp_ms: row.get(1)?,
+                session_id: row.get(2)?,
+                hostname: row.get(3)?,
+                cwd: row.get(4)?,
+                duration_ms: row.get(5)?,
+                exit_status: row.get(6)?,
+            })
+        })?;
+
+        let mut entries = Vec::new();
+        for (index, row) in rows.enumerate() {
+            let row = row?;
+
+            let timestamp = row.start_timestamp_ms.and_then(|ms| {
+                OffsetDateTime::from_unix_timestamp_nanos(i128::from(ms) * 1_000_000).ok()
+            });
+
+            let Some(mut entry) = self.make_entry(row.command_line, timestamp, index + 1) else {
+                continue;
+            };
+
+            entry.duration = row.duration_ms.map(Duration::milliseconds);
+            entry.exit_status = row.exit_status.map(|code| code as i32);
+            entry.session = row.session_id.map(|id| id.to_string());
+            entry.hostname = row.hostname.filter(|h| !h.is_empty());
+            entry.cwd = row.cwd.filter(|c| !c.is_empty());
+
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+}
+
+impl HistoryParser for NushellHistoryParser {
+    fn parse_file(&self, path: &Path) -> Result<Vec<HistoryEntry>> {
+        let mut file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to read history file: {:?}", path))?;
+        let mut head = Vec::new();
+        (&mut file)
+            .take(SQLITE_HEADER.len() as u64)
+            .read_to_end(&mut head)
+            .with_context(|| format!("Failed to read history file: {:?}", path))?;
+
+        if head == SQLITE_HEADER {
+            let conn = Connection::open_with_flags(
+                path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )
+            .with_context(|| format!("Failed to open nushell database: {:?}", path))?;
+
+            return self.read_rows(&conn);
+        }
+
+        let mut bytes = head;
+        file.read_to_end(&mut bytes)
+            .with_context(|| format!("Failed to read history file: {:?}", path))?;
+        let content = String::from_utf8_lossy(&bytes);
+        Ok(self.parse_content(&content))
+    }
+
+    fn parse_str(&self, content: &str) -> Result<Vec<HistoryEntry>> {
+        Ok(self.parse_content(content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = "CREATE TABLE history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        command_line TEXT NOT NULL,
+        start_timestamp INTEGER,
+        session_id INTEGER,
+        hostname TEXT,
+        cwd TEXT,
+        duration_ms INTEGER,
+        exit_status INTEGER,
+        more_info TEXT
+    )";
+
+    #[test]
+    fn parse_plaintext_format() {
+        let parser = NushellHistoryParser::new();
+        let entries = parser.parse_content("ls | where size > 1kb\ngit status\n\n");
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].command, "ls");
+        assert_eq!(entries[1].command, "git");
+        assert!(entries[0].timestamp.is_none());
+    }
+
+    #[test]
+    fn plaintext_newline_escape() {
+        let parser = NushellHistoryParser::new().preserve_full_lines(true);
+        let entries = parser.parse_content("def greet [] {<\\n>  print hi<\\n>}\n");
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].command, "def");
+        assert_eq!(entries[0].full_line, "def greet [] {\n  print hi\n}");
+    }
+
+    #[test]
+    fn sqlite_rows_map_to_entries() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.execute_batch(
+            "INSERT INTO history (command_line, start_timestamp, session_id, hostname, cwd, duration_ms, exit_status)
+             VALUES ('cargo test', 1702400100000, 7, 'box', '/src', 2500, 101),
+                    ('git status', 1702400000000, 7, 'box', '/src', 40, 0),
+                    ('ls', NULL, NULL, NULL, NULL, NULL, NULL)",
+        )
+        .unwrap();
+
+        let entries = NushellHistoryParser::new().read_rows(&conn).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        // NULL timestamps sort first
+        assert_eq!(entries[0].command, "ls");
+        assert!(entries[0].timestamp.is_none());
+        assert!(entries[0].exit_status.is_none());
+
+        assert_eq!(entries[1].command, "git");
+        assert_eq!(entries[1].timestamp.unwrap().unix_timestamp(), 1702400000);
+        assert_eq!(entries[2].duration, Some(Duration::milliseconds(2500)));
+        assert_eq!(entries[2].exit_status, Some(101));
+        assert_eq!(entries[2].session.as_deref(), Some("7"));
+        assert_eq!(entries[2].cwd.as_deref(), Some("/src"));
+    }
+
+    #[test]
+    fn parse_file_sniffs_sqlite() {
+        let path = std::env::temp_dir().join("absurdtty_test_nushell.sqlite3");
+        let _ = std::fs::remove_file(&path);
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(SCHEMA).unwrap();
+            conn.execute_batch(
+                "INSERT INTO history (command_line, start_timestamp) VALUES ('ls', 1702400000000)",
+            )
+            .unwrap();
+        }
+
+        let entries = NushellHistoryParser::new().parse_file(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].timestamp.is_some());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
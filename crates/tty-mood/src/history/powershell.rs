@@ -0,0 +1,143 @@
+//! PowerShell (PSReadLine) history parser.
+//!
+//! PSReadLine appends every accepted command to
+//! `ConsoleHost_history.txt`, one command per line. Commands spanning
+//! several lines are written with a trailing backtick on every line
+//! but the last:
+//!
+//! ```text
+//! Get-ChildItem -Recurse |`
+//!   Where-Object Length -gt 1mb
+//! git status
+//! ```
+//!
+//! The file carries no timestamps, durations or exit codes, so only
+//! the commands themselves can be analyzed.
+
+use super::{entry::HistoryEntry, HistoryParser};
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Backtick PSReadLine writes before each embedded newline.
+const CONTINUATION: char = '`';
+
+/// Parser for PSReadLine's `ConsoleHost_history.txt`.
+#[derive(Debug, Default)]
+pub struct PowerShellHistoryParser {
+    /// Whether to preserve full command lines (for internal analysis)
+    preserve_full_line: bool,
+}
+
+impl PowerShellHistoryParser {
+    /// Create a new PowerShell history parser.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure whether to preserve full command lines.
+    pub fn preserve_full_lines(mut self, preserve: bool) -> Self {
+        self.preserve_full_line = preserve;
+        self
+    }
+
+    /// Build an entry from a complete command, starting at `line_number`.
+    fn make_entry(&self, command: String, line_number: usize) -> Option<HistoryEntry> {
+        if command.trim().is_empty() {
+            return None;
+        }
+
+        let mut entry = HistoryEntry::from_command_line(&command, None, line_number);
+
+        if self.preserve_full_line {
+            entry = entry.with_full_line(command);
+        }
+
+        Some(entry)
+    }
+
+    /// Parse content, joining backtick continuations.
+    fn parse_content(&self, content: &str) -> Vec<HistoryEntry> {
+        let mut entries = Vec::new();
+        let mut pending: Option<(String, usize)> = None;
+
+        for (index, line) in content.lines().enumerate() {
+            let (command, _) = pending.get_or_insert_with(|| (String::new(), index + 1));
+
+            if let Some(partial) = line.strip_suffix(CONTINUATION) {
+                command.push_str(partial);
+                command.push('\n');
+                continue;
+            }
+
+            command.push_str(line);
+            if let Some((command, start)) = pending.take() {
+                entries.extend(self.make_entry(command, start));
+            }
+        }
+
+        // A dangling continuation at end of file is still a command
+        if let Some((command, start)) = pending {
+            entries.extend(self.make_entry(command.trim_end().to_string(), start));
+        }
+
+        entries
+    }
+}
+
+impl HistoryParser for PowerShellHistoryParser {
+    fn parse_file(&self, path: &Path) -> Result<Vec<HistoryEntry>> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read history file: {:?}", path))?;
+        let content = String::from_utf8_lossy(&bytes);
+        Ok(self.parse_content(&content))
+    }
+
+    fn parse_str(&self, content: &str) -> Result<Vec<HistoryEntry>> {
+        Ok(self.parse_content(content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_command_per_line() {
+        let parser = PowerShellHistoryParser::new();
+        let entries = parser.parse_content("git status\r\nGet-ChildItem\n\ncargo build\n");
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].command, "git");
+        assert_eq!(entries[1].command, "Get-ChildItem");
+        assert_eq!(entries[2].line_number, 4);
+        assert!(entries.iter().all(|e| e.timestamp.is_none()));
+    }
+
+    #[test]
+    fn backtick_continuations_are_joined() {
+        let parser = PowerShellHistoryParser::new().preserve_full_lines(true);
+        let content = "Get-ChildItem -Recurse |`\n  Where-Object Length -gt 1mb |`\n  Remove-Item\nls\n";
+        let entries = parser.parse_content(content);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].full_line,
+            "Get-ChildItem -Recurse |\n  Where-Object Length -gt 1mb |\n  Remove-Item"
+        );
+        assert_eq!(
+            entries[0].commands,
+            ["Get-ChildItem", "Where-Object", "Remove-Item"]
+        );
+        assert_eq!(entries[1].command, "ls");
+        assert_eq!(entries[1].line_number, 4);
+    }
+
+    #[test]
+    fn dangling_continuation_is_kept() {
+        let parser = PowerShellHistoryParser::new();
+        let entries = parser.parse_content("git commit `");
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].command, "git");
+    }
+}
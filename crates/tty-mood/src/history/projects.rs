@@ -0,0 +1,293 @@
+//! Project tracking.
+//!
+//! Ten `cd`s inside one repository are not the same as hopping between
+//! five projects. Each entry is tagged with the project it ran in: the
+//! nearest directory above it holding a `.git`, `Cargo.toml` or
+//! `package.json`.
+//!
+//! The directory comes from the source where it records one (Atuin and
+//! nushell store the working directory). Otherwise it is followed
+//! through `cd` and `pushd` lines, as far as their targets can be
+//! worked out: absolute paths, `~`, `-` and paths relative to a
+//! directory already known.
+//!
+//! Projects are identified by a fingerprint of their root path only;
+//! names and paths never leave memory. Working directories that are
+//! already checksums (the `tty-mood init` event log, analyzed on its
+//! own) can't be resolved and count as places of their own.
+
+use super::{fingerprint, lexer, HistoryEntry, HistoryParser};
+use anyhow::Result;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Files and directories that mark a project root.
+const PROJECT_MARKERS: &[&str] = &[".git", "Cargo.toml", "package.json"];
+
+/// Commands that change the working directory.
+const CD_COMMANDS: &[&str] = &["cd", "pushd"];
+
+/// Finds project roots, remembering every directory it has looked at.
+#[derive(Debug, Default)]
+pub struct ProjectResolver {
+    /// Never a project root: a dotfiles repository in `~` would
+    /// otherwise swallow every directory below it
+    home: Option<PathBuf>,
+    roots: RefCell<HashMap<PathBuf, Option<u64>>>,
+}
+
+impl ProjectResolver {
+    /// Create a resolver for the current user.
+    pub fn new() -> Self {
+        Self::with_home(dirs::home_dir())
+    }
+
+    /// Create a resolver with an explicit home directory.
+    pub fn with_home(home: Option<PathBuf>) -> Self {
+        Self {
+            home,
+            roots: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Tag entries with the project they ran in, in history order.
+    ///
+    /// The working directory is tracked separately for each shell session.
+    pub fn resolve_all(&self, entries: &mut [HistoryEntry]) {
+        let mut cwds: HashMap<Option<String>, Cwd> = HashMap::new();
+
+        for entry in entries {
+            let cwd = cwds.entry(entry.session.clone()).or_default();
+
+            match entry.cwd.as_deref() {
+                Some(dir) if dir.starts_with('/') => cwd.current = Some(PathBuf::from(dir)),
+                // A checksum, not a path: the directory is its own place
+                Some(opaque) => {
+                    entry.project = Some(fingerprint(opaque.as_bytes()));
+                    continue;
+                }
+                None => {}
+            }
+
+            entry.project = cwd.current.as_deref().and_then(|dir| self.project_of(dir));
+
+            if CD_COMMANDS.contains(&entry.command_name()) && !entry.full_line.is_empty() {
+                let words = lexer::split_words(&entry.full_line);
+                let target = words
+                    .iter()
+                    .skip(1)
+                    .find(|w| !w.starts_with('-') || *w == "-");
+                cwd.change(target.map(String::as_str), self.home.as_deref());
+            }
+        }
+    }
+
+    /// Fingerprint of the project root containing `dir`, if any.
+    fn project_of(&self, dir: &Path) -> Option<u64> {
+        if let Some(project) = self.roots.borrow().get(dir) {
+            return *project;
+        }
+
+        let project = dir
+            .ancestors()
+            .take_while(|d| Some(*d) != self.home.as_deref())
+            .find(|d| PROJECT_MARKERS.iter().any(|m| d.join(m).exists()))
+            .map(|root| fingerprint(root.as_os_str().as_encoded_bytes()));
+
+        self.roots.borrow_mut().insert(dir.to_path_buf(), project);
+        project
+    }
+}
+
+/// Working directory of one shell session, as far as it is known.
+#[derive(Debug, Default)]
+struct Cwd {
+    current: Option<PathBuf>,
+    previous: Option<PathBuf>,
+}
+
+impl Cwd {
+    /// Follow a `cd` to `target` (None for a bare `cd`).
+    fn change(&mut self, target: Option<&str>, home: Option<&Path>) {
+        let next = match target {
+            None | Some("~") => home.map(Path::to_path_buf),
+            Some("-") => self.previous.clone(),
+            Some(path) if path.starts_with('/') => Some(PathBuf::from(path)),
+            Some(path) => match path.strip_prefix("~/") {
+                Some(rest) => home.map(|h| h.join(rest)),
+                None => self.current.as_ref().map(|dir| dir.join(path)),
+            },
+        };
+
+        self.previous = self.current.take();
+        self.current = next.map(|dir| normalize(&dir));
+    }
+}
+
+/// Resolve `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normal.pop();
+            }
+            other => normal.push(other),
+        }
+    }
+    normal
+}
+
+/// Wraps a parser, tagging what it produces with projects.
+///
+/// Projects are resolved before entries are cached, since the cache
+/// keeps neither command lines nor directories. A tail read by the
+/// incremental reader starts without a known directory, so its entries
+/// before the first `cd` have no project.
+pub struct ProjectParser {
+    inner: Box<dyn HistoryParser>,
+    resolver: ProjectResolver,
+}
+
+impl ProjectParser {
+    /// Wrap a parser. It should preserve full lines for `cd` to be followed.
+    pub fn new(inner: Box<dyn HistoryParser>) -> Self {
+        Self {
+            inner,
+            resolver: ProjectResolver::new(),
+        }
+    }
+
+    fn tag(&self, mut entries: Vec<HistoryEntry>) -> Vec<HistoryEntry> {
+        self.resolver.resolve_all(&mut entries);
+        entries
+    }
+}
+
+impl HistoryParser for ProjectParser {
+    fn parse_file(&self, path: &Path) -> Result<Vec<HistoryEntry>> {
+        self.inner.parse_file(path).map(|e| self.tag(e))
+    }
+
+    fn parse_str(&self, content: &str) -> Result<Vec<HistoryEntry>> {
+        self.inner.parse_str(content).map(|e| self.tag(e))
+    }
+
+    fn parse_bytes(&self, bytes: &[u8]) -> Result<Vec<HistoryEntry>> {
+        self.inner.parse_bytes(bytes).map(|e| self.tag(e))
+    }
+
+    fn cache_key(&self) -> u64 {
+        self.inner.cache_key()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A home directory with two projects, one of them nested in a folder.
+    struct Fixture {
+        home: PathBuf,
+    }
+
+    impl Fixture {
+        fn new(name: &str) -> Self {
+            let home = std::env::temp_dir().join(format!("absurdtty_test_projects_{}", name));
+            let _ = std::fs::remove_dir_all(&home);
+            std::fs::create_dir_all(home.join("src/alpha/.git")).unwrap();
+            std::fs::create_dir_all(home.join("src/alpha/docs")).unwrap();
+            std::fs::create_dir_all(home.join("src/beta/crates/core")).unwrap();
+            std::fs::write(home.join("src/beta/Cargo.toml"), "").unwrap();
+            // A dotfiles repository in home must not count
+            std::fs::create_dir_all(home.join(".git")).unwrap();
+            Self { home }
+        }
+
+        fn resolver(&self) -> ProjectResolver {
+            ProjectResolver::with_home(Some(self.home.clone()))
+        }
+
+        fn root(&self, project: &str) -> Option<u64> {
+            let root = self.home.join("src").join(project);
+            Some(fingerprint(root.as_os_str().as_encoded_bytes()))
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.home);
+        }
+    }
+
+    fn entries(lines: &[&str]) -> Vec<HistoryEntry> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                HistoryEntry::from_command_line(line, None, i + 1).with_full_line(line.to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cd_targets_are_followed_to_project_roots() {
+        let fixture = Fixture::new("cd");
+        let mut entries = entries(&[
+            "cd ~/src/alpha",
+            "git status",
+            "cd docs",
+            "ls",
+            "cd ../../beta/crates/core",
+            "cargo test",
+            "cd -",
+            "ls",
+            "cd",
+            "ls",
+        ]);
+        fixture.resolver().resolve_all(&mut entries);
+
+        let projects: Vec<_> = entries.iter().map(|e| e.project).collect();
+        let (alpha, beta) = (fixture.root("alpha"), fixture.root("beta"));
+        assert_eq!(
+            projects,
+            [None, alpha, alpha, alpha, alpha, beta, beta, alpha, alpha, None]
+        );
+    }
+
+    #[test]
+    fn unknown_relative_targets_lose_track() {
+        let fixture = Fixture::new("relative");
+        let mut entries = entries(&["cd somewhere", "ls"]);
+        fixture.resolver().resolve_all(&mut entries);
+
+        assert!(entries.iter().all(|e| e.project.is_none()));
+    }
+
+    #[test]
+    fn recorded_cwd_wins_over_cd() {
+        let fixture = Fixture::new("cwd");
+        let mut entries = entries(&["cd /nonexistent", "make", "ls"]);
+        entries[1].cwd = Some(fixture.home.join("src/beta").to_string_lossy().to_string());
+        entries[2].cwd = Some("3735928559".to_string());
+        fixture.resolver().resolve_all(&mut entries);
+
+        assert_eq!(entries[1].project, fixture.root("beta"));
+        assert_eq!(entries[2].project, Some(fingerprint(b"3735928559")));
+    }
+
+    #[test]
+    fn sessions_track_their_own_directory() {
+        let fixture = Fixture::new("sessions");
+        let mut entries = entries(&["cd ~/src/alpha", "cd ~/src/beta", "ls", "ls"]);
+        for (entry, session) in entries.iter_mut().zip(["a", "b", "a", "b"]) {
+            entry.session = Some(session.to_string());
+        }
+        fixture.resolver().resolve_all(&mut entries);
+
+        assert_eq!(entries[2].project, fixture.root("alpha"));
+        assert_eq!(entries[3].project, fixture.root("beta"));
+    }
+}
@@ -0,0 +1,195 @@
+//! Session segmentation.
+//!
+//! Splits a merged timeline into working sessions, so a frantic Tuesday
+//! evening isn't averaged away by a calm week. Entries are grouped by
+//! source and session ID first (Atuin, nushell and asciinema record
+//! one), then split wherever the shell sat idle for longer than the
+//! idle gap. A command that is still running doesn't count as idle.
+
+use super::HistoryEntry;
+use std::collections::BTreeMap;
+use time::{Duration, OffsetDateTime};
+
+/// A run of commands without a long pause in between.
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// Entries in timestamp order
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl Session {
+    /// When the first command was run (None for untimed history).
+    pub fn start(&self) -> Option<OffsetDateTime> {
+        self.entries.first().and_then(|e| e.timestamp)
+    }
+
+    /// When the last command finished, or started if its duration is unknown.
+    pub fn end(&self) -> Option<OffsetDateTime> {
+        self.entries.iter().filter_map(finished_at).max()
+    }
+}
+
+/// Split entries into sessions, ordered by start time.
+///
+/// Entries without timestamps can't be split by idle time; each source
+/// session's untimed entries form one session of their own.
+pub fn segment(entries: Vec<HistoryEntry>, idle_gap: Duration) -> Vec<Session> {
+    let mut groups: BTreeMap<(usize, Option<String>), Vec<HistoryEntry>> = BTreeMap::new();
+    for entry in entries {
+        groups
+            .entry((entry.source, entry.session.clone()))
+            .or_default()
+            .push(entry);
+    }
+
+    let mut sessions = Vec::new();
+    for (_, mut group) in groups {
+        group.sort_by_key(|e| e.timestamp);
+
+        let mut current: Vec<HistoryEntry> = Vec::new();
+        let mut last_active: Option<OffsetDateTime> = None;
+
+        for entry in group {
+            let idle = match (last_active, entry.timestamp) {
+                (Some(last), Some(ts)) => ts - last > idle_gap,
+                // Untimed entries sort first; the first timed one starts afresh
+                (None, Some(_)) => !current.is_empty(),
+                _ => false,
+            };
+
+            if idle {
+                sessions.push(Session {
+                    entries: std::mem::take(&mut current),
+                });
+            }
+
+            if let Some(done) = finished_at(&entry) {
+                last_active = Some(last_active.map_or(done, |last| last.max(done)));
+            }
+            current.push(entry);
+        }
+
+        if !current.is_empty() {
+            sessions.push(Session { entries: current });
+        }
+    }
+
+    sessions.sort_by_key(|s| s.start());
+    sessions
+}
+
+/// Group entries by the shell they were typed in: source and session ID.
+///
+/// Entries keep their order within each group. Commands from two
+/// terminals interleave in a merged timeline; anything that looks at
+/// consecutive commands should look at one group at a time.
+pub fn by_shell(entries: &[HistoryEntry]) -> Vec<Vec<&HistoryEntry>> {
+    let mut groups: BTreeMap<(usize, Option<&str>), Vec<&HistoryEntry>> = BTreeMap::new();
+    for entry in entries {
+        groups
+            .entry((entry.source, entry.session.as_deref()))
+            .or_default()
+            .push(entry);
+    }
+    groups.into_values().collect()
+}
+
+/// When a command finished, as far as we know.
+///
+/// Falls back to the start when the duration is too large to add,
+/// as it is in a corrupt history line.
+fn finished_at(entry: &HistoryEntry) -> Option<OffsetDateTime> {
+    entry.timestamp.map(|ts| {
+        ts.checked_add(entry.duration.unwrap_or(Duration::ZERO))
+            .unwrap_or(ts)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDLE: Duration = Duration::minutes(30);
+
+    fn entry_at(minute: i64) -> HistoryEntry {
+        let ts = OffsetDateTime::from_unix_timestamp(1702400000 + minute * 60).ok();
+        HistoryEntry::new("ls".to_string(), ts, 1)
+    }
+
+    fn sizes(sessions: &[Session]) -> Vec<usize> {
+        sessions.iter().map(|s| s.entries.len()).collect()
+    }
+
+    #[test]
+    fn idle_gaps_split_sessions() {
+        let entries = vec![
+            entry_at(0),
+            entry_at(5),
+            entry_at(12),
+            entry_at(90),
+            entry_at(95),
+        ];
+        let sessions = segment(entries, IDLE);
+
+        assert_eq!(sizes(&sessions), [3, 2]);
+        assert_eq!(sessions[0].start(), entry_at(0).timestamp);
+        assert_eq!(sessions[0].end(), entry_at(12).timestamp);
+        assert_eq!(sessions[1].start(), entry_at(90).timestamp);
+    }
+
+    #[test]
+    fn running_commands_are_not_idle() {
+        let mut build = entry_at(10);
+        build.duration = Some(Duration::minutes(60));
+        let entries = vec![entry_at(0), build, entry_at(75)];
+
+        let sessions = segment(entries, IDLE);
+        assert_eq!(sizes(&sessions), [3]);
+        assert_eq!(sessions[0].end(), entry_at(75).timestamp);
+    }
+
+    #[test]
+    fn oversized_durations_end_at_the_start() {
+        let mut corrupt = entry_at(10);
+        corrupt.duration = Some(Duration::seconds(9_000_000_000_000_000));
+        let entries = vec![entry_at(0), corrupt, entry_at(75)];
+
+        let sessions = segment(entries, IDLE);
+        assert_eq!(sizes(&sessions), [2, 1]);
+        assert_eq!(sessions[0].end(), entry_at(10).timestamp);
+    }
+
+    #[test]
+    fn session_ids_keep_interleaved_terminals_apart() {
+        let tagged = |minute, id: &str| {
+            let mut entry = entry_at(minute);
+            entry.session = Some(id.to_string());
+            entry
+        };
+        let entries = vec![
+            tagged(0, "a"),
+            tagged(1, "b"),
+            tagged(2, "a"),
+            tagged(3, "b"),
+            tagged(4, "b"),
+        ];
+
+        let sessions = segment(entries, IDLE);
+        assert_eq!(sizes(&sessions), [2, 3]);
+        assert!(sessions[1]
+            .entries
+            .iter()
+            .all(|e| e.session.as_deref() == Some("b")));
+    }
+
+    #[test]
+    fn untimed_entries_form_their_own_session() {
+        let untimed = HistoryEntry::new("ls".to_string(), None, 1);
+        let entries = vec![untimed.clone(), untimed, entry_at(0), entry_at(1)];
+
+        let sessions = segment(entries, IDLE);
+        assert_eq!(sizes(&sessions), [2, 2]);
+        assert!(sessions[0].start().is_none());
+        assert!(sessions[1].start().is_some());
+    }
+}
@@ -15,11 +15,20 @@
 //! : 1702400200:0;echo "hello\
 //! world"
 //! ```
+//!
+//! ZSH writes history "metafied": bytes that are special to its
+//! internals (NUL and 0x83..=0xA2) are stored as the `Meta` byte 0x83
+//! followed by the original byte XOR 0x20. Many UTF-8 continuation
+//! bytes fall into that range (`ß` is `C3 9F`, `日` is `E6 97 A5`), so
+//! the raw bytes must be un-metafied before decoding.
 
 use super::{entry::HistoryEntry, HistoryParser};
 use anyhow::{Context, Result};
 use std::path::Path;
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
+
+/// ZSH's `Meta` byte, marking the next byte as XORed with 0x20.
+const META: u8 = 0x83;
 
 /// Parser for ZSH extended history format.
 #[derive(Debug, Default)]
@@ -35,7 +44,6 @@ impl ZshHistoryParser {
     }
 
     /// Configure whether to preserve full command lines.
-    #[allow(dead_code)]
     pub fn preserve_full_lines(mut self, preserve: bool) -> Self {
         self.preserve_full_line = preserve;
         self
@@ -49,8 +57,7 @@ impl ZshHistoryParser {
         if !line.starts_with(": ") {
             // Simple format (no timestamp) - just the command
             if !line.is_empty() && !line.starts_with('#') {
-                let command = extract_command_name(line);
-                return Some(HistoryEntry::new(command, None, line_number));
+                return Some(HistoryEntry::from_command_line(line, None, line_number));
             }
             return None;
         }
@@ -73,11 +80,11 @@ impl ZshHistoryParser {
             .ok()
             .and_then(|ts| OffsetDateTime::from_unix_timestamp(ts).ok());
 
-        let duration = duration_str.and_then(|d| d.parse::<u64>().ok());
-
-        let command = extract_command_name(full_command);
+        let duration = duration_str
+            .and_then(|d| d.parse::<i64>().ok())
+            .map(Duration::seconds);
 
-        let mut entry = HistoryEntry::new(command, timestamp, line_number);
+        let mut entry = HistoryEntry::from_command_line(full_command, timestamp, line_number);
         entry.duration = duration;
 
         if self.preserve_full_line {
@@ -133,54 +140,41 @@ impl ZshHistoryParser {
 
 impl HistoryParser for ZshHistoryParser {
     fn parse_file(&self, path: &Path) -> Result<Vec<HistoryEntry>> {
-        // Read as bytes first, then convert with lossy UTF-8 handling
-        // ZSH history can contain non-UTF-8 bytes in some cases
         let bytes = std::fs::read(path)
             .with_context(|| format!("Failed to read history file: {:?}", path))?;
-        let content = String::from_utf8_lossy(&bytes);
-        Ok(self.parse_content(&content))
+        self.parse_bytes(&bytes)
     }
 
     fn parse_str(&self, content: &str) -> Result<Vec<HistoryEntry>> {
         Ok(self.parse_content(content))
     }
-}
 
-/// Extract just the command name from a full command line.
-///
-/// This extracts the first word, handling:
-/// - Leading whitespace
-/// - Environment variable assignments (FOO=bar cmd)
-/// - Sudo/command prefixes
-fn extract_command_name(line: &str) -> String {
-    let line = line.trim();
-
-    // Skip empty or comment lines
-    if line.is_empty() || line.starts_with('#') {
-        return line.to_string();
+    fn parse_bytes(&self, bytes: &[u8]) -> Result<Vec<HistoryEntry>> {
+        // Un-metafy first, then convert with lossy UTF-8 handling:
+        // history written under a non-UTF-8 locale may still be invalid
+        let bytes = unmetafy(bytes);
+        Ok(self.parse_content(&String::from_utf8_lossy(&bytes)))
     }
+}
 
-    // Handle env var assignments at start: FOO=bar BAZ=qux command
-    let mut words = line.split_whitespace().peekable();
-    while let Some(word) = words.peek() {
-        if word.contains('=') && !word.starts_with('-') {
-            words.next();
+/// Undo zsh's metafication of history bytes.
+///
+/// A trailing `Meta` with nothing after it is dropped.
+pub(super) fn unmetafy(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter();
+
+    while let Some(&byte) = iter.next() {
+        if byte == META {
+            if let Some(&next) = iter.next() {
+                out.push(next ^ 0x20);
+            }
         } else {
-            break;
+            out.push(byte);
         }
     }
 
-    // Get the actual command
-    let cmd = words.next().unwrap_or("");
-
-    // Check for prefix commands that take another command as argument
-    let prefix_commands = ["sudo", "command", "builtin", "exec", "env", "nice", "nohup", "time"];
-    if prefix_commands.contains(&cmd) {
-        // Return the next word (the actual command)
-        words.next().unwrap_or(cmd).to_string()
-    } else {
-        cmd.to_string()
-    }
+    out
 }
 
 #[cfg(test)]
@@ -219,14 +213,12 @@ mod tests {
     }
 
     #[test]
-    fn extract_command_skips_env_vars() {
-        assert_eq!(extract_command_name("FOO=bar mycommand"), "mycommand");
-        assert_eq!(extract_command_name("A=1 B=2 gcc main.c"), "gcc");
-    }
+    fn commands_from_compound_lines() {
+        let parser = ZshHistoryParser::new();
+        let entries = parser.parse_content(": 1702400000:0;FOO=\"a b\" make | tee log && ls\n");
 
-    #[test]
-    fn extract_command_handles_sudo() {
-        assert_eq!(extract_command_name("sudo pacman -S vim"), "pacman");
+        assert_eq!(entries[0].command, "make");
+        assert_eq!(entries[0].commands, ["make", "tee", "ls"]);
     }
 
     #[test]
@@ -257,8 +249,51 @@ fc-cache -fv
         assert_eq!(entries[0].command, "ls");
         assert_eq!(entries[1].command, "source");
         assert_eq!(entries[2].command, "micro");
-        // Multi-line command starting with comment
-        assert!(entries[3].command.starts_with('#'));
+        // Multi-line command starting with comment: the comment is skipped
+        assert_eq!(entries[3].command, "pacman");
+        assert_eq!(entries[3].commands, ["pacman", "fc-cache"]);
+    }
+
+    #[test]
+    fn unmetafy_restores_bytes() {
+        // "ß" = C3 9F, stored as C3 83 BF
+        assert_eq!(unmetafy(&[0xC3, 0x83, 0xBF]), "ß".as_bytes());
+        // Plain ASCII and unaffected UTF-8 pass through
+        assert_eq!(unmetafy("ls ü".as_bytes()), "ls ü".as_bytes());
+        assert_eq!(unmetafy(&[b'a', META]), b"a");
     }
-}
 
+    #[test]
+    fn german_fixture_decodes() {
+        let parser = ZshHistoryParser::new().preserve_full_lines(true);
+        let entries = parser
+            .parse_bytes(include_bytes!("../../tests/fixtures/zsh_history_german"))
+            .unwrap();
+
+        let commands: Vec<_> = entries.iter().map(|e| e.command.as_str()).collect();
+        assert_eq!(
+            commands,
+            ["./größe_prüfen.sh", "mkdir", "grep", "./straßenbahn", "cd"]
+        );
+        assert_eq!(entries[1].full_line, "mkdir Übungsblätter");
+        assert_eq!(entries[2].full_line, "grep -ri \"Schließfach\" Maßnahmen/");
+        assert!(entries.iter().all(|e| !e.full_line.contains('\u{FFFD}')));
+    }
+
+    #[test]
+    fn cjk_fixture_decodes() {
+        let parser = ZshHistoryParser::new().preserve_full_lines(true);
+        let entries = parser
+            .parse_bytes(include_bytes!("../../tests/fixtures/zsh_history_cjk"))
+            .unwrap();
+
+        let commands: Vec<_> = entries.iter().map(|e| e.command.as_str()).collect();
+        assert_eq!(commands, ["cd", "./日本語テスト.sh", "echo", "mv", "git"]);
+        assert_eq!(entries[0].full_line, "cd ~/文档/项目");
+        assert_eq!(entries[3].full_line, "mv 사진.jpg 写真/");
+        assert_eq!(entries[4].full_line, "git commit -m \"修正バグ\"");
+        assert!(entries.iter().all(|e| !e.full_line.contains('\u{FFFD}')));
+        // Multi-byte commands are not mistaken for short typos
+        assert!(!entries[1].looks_like_typo());
+    }
+}
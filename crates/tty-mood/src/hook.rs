@@ -0,0 +1,130 @@
+//! Shell hooks for the absurdtty event log.
+//!
+//! History files don't say whether a command failed or how long it
+//! ran (zsh records durations, nobody but Atuin records exit status).
+//! `tty-mood init <shell>` prints a hook that appends exactly that to
+//! an event log absurdtty owns; see [`crate::history::events`] for the
+//! format. The hooks write only the command name, never its arguments,
+//! and only a checksum of the working directory.
+
+use crate::history::events;
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+
+/// Placeholder in the hook scripts for the quoted event log path.
+const LOG_PLACEHOLDER: &str = "@EVENT_LOG@";
+
+/// Shells `tty-mood init` can print hooks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookShell {
+    Zsh,
+    Bash,
+    Fish,
+}
+
+impl HookShell {
+    /// The hook script, logging to `log`.
+    pub fn script(self, log: &Path) -> String {
+        let template = match self {
+            HookShell::Zsh => include_str!("hooks/absurdtty.zsh"),
+            HookShell::Bash => include_str!("hooks/absurdtty.bash"),
+            HookShell::Fish => include_str!("hooks/absurdtty.fish"),
+        };
+
+        template.replace(LOG_PLACEHOLDER, &self.quote(&log.to_string_lossy()))
+    }
+
+    /// Quote a string as a single-quoted shell word.
+    fn quote(self, value: &str) -> String {
+        match self {
+            HookShell::Zsh | HookShell::Bash => format!("'{}'", value.replace('\'', r"'\''")),
+            HookShell::Fish => format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'")),
+        }
+    }
+}
+
+impl std::str::FromStr for HookShell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "zsh" => Ok(HookShell::Zsh),
+            "bash" => Ok(HookShell::Bash),
+            "fish" => Ok(HookShell::Fish),
+            _ => Err(format!(
+                "Unsupported shell: {}. Use 'zsh', 'bash' or 'fish'.",
+                s
+            )),
+        }
+    }
+}
+
+/// Create the event log with its header, readable only by the user.
+///
+/// An existing log is left alone.
+pub fn ensure_event_log(path: &Path) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    match options.open(path) {
+        Ok(mut file) => writeln!(file, "{}", events::HEADER)
+            .with_context(|| format!("Failed to write event log: {:?}", path)),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(()),
+        Err(e) => Err(e).with_context(|| format!("Failed to create event log: {:?}", path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_embeds_quoted_log_path() {
+        let log = Path::new("/home/o'brien/.local/share/absurdtty/events.log");
+
+        let zsh = HookShell::Zsh.script(log);
+        assert!(zsh.contains(r"'/home/o'\''brien/.local/share/absurdtty/events.log'"));
+        assert!(zsh.contains("add-zsh-hook preexec"));
+        assert!(!zsh.contains(LOG_PLACEHOLDER));
+
+        let fish = HookShell::Fish.script(log);
+        assert!(fish.contains(r"'/home/o\'brien/.local/share/absurdtty/events.log'"));
+        assert!(fish.contains("--on-event fish_postexec"));
+
+        assert!(HookShell::Bash.script(log).contains("PROMPT_COMMAND"));
+    }
+
+    #[test]
+    fn event_log_created_once() {
+        let path =
+            std::env::temp_dir().join(format!("absurdtty_test_events_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        ensure_event_log(&path).unwrap();
+        std::fs::write(
+            &path,
+            format!("{}\n1702400000\t5\t0\t1\tls\n", events::HEADER),
+        )
+        .unwrap();
+        ensure_event_log(&path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(events::HEADER));
+        assert!(content.contains("ls"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn shell_names() {
+        assert_eq!("ZSH".parse::<HookShell>().unwrap(), HookShell::Zsh);
+        assert!("tcsh".parse::<HookShell>().is_err());
+    }
+}
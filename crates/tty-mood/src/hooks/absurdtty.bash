@@ -0,0 +1,55 @@
+# absurdtty event log hook for bash (5.0 or later).
+# Load it from ~/.bashrc with: eval "$(tty-mood init bash)"
+#
+# Appends one line per command to the event log:
+# start time, duration (ms), exit status, cwd hash, command name.
+# Arguments and directory names are never written.
+#
+# Commands are caught with a DEBUG trap, which replaces any DEBUG trap
+# already set.
+
+_absurdtty_log=@EVENT_LOG@
+_absurdtty_armed= _absurdtty_next= _absurdtty_start= _absurdtty_cmd= _absurdtty_pwd= _absurdtty_cwd=
+# A `history` line: number, `*` if edited, the command line
+_absurdtty_entry='^ *([0-9]+)[* ] (.*)'
+
+_absurdtty_preexec() {
+    # An empty command line runs PROMPT_COMMAND straight away
+    [[ -n $_absurdtty_armed && -z $COMP_LINE && $BASH_COMMAND != _absurdtty_precmd ]] || return 0
+    _absurdtty_armed=
+    local start=${EPOCHREALTIME/,/.}
+    # The line as typed: BASH_COMMAND is one simple command, after aliases
+    [[ $(HISTTIMEFORMAT= builtin history 1) =~ $_absurdtty_entry ]] || return 0
+    # Lines kept out of history (leading space, ignoredups) are not logged
+    (( BASH_REMATCH[1] >= _absurdtty_next )) || return 0
+    local typed=${BASH_REMATCH[2]}
+    [[ $typed != [[:space:]]* ]] || return 0
+    _absurdtty_cmd=${typed%%[[:space:]]*}
+    _absurdtty_start=$start
+    if [[ $PWD != "$_absurdtty_pwd" ]]; then
+        _absurdtty_pwd=$PWD
+        _absurdtty_cwd=$(printf %s "$PWD" | cksum)
+        _absurdtty_cwd=${_absurdtty_cwd%% *}
+    fi
+}
+
+_absurdtty_precmd() {
+    local exit=$?
+    _absurdtty_armed=
+    if [[ -n $_absurdtty_start ]]; then
+        local start_us=${_absurdtty_start/./} now_us=${EPOCHREALTIME/[.,]/}
+        printf '%s\t%d\t%d\t%s\t%s\n' "$_absurdtty_start" $(( (now_us - start_us) / 1000 )) \
+            "$exit" "$_absurdtty_cwd" "$_absurdtty_cmd" >> "$_absurdtty_log"
+        _absurdtty_start=
+    fi
+    return $exit
+}
+
+# Runs last in PROMPT_COMMAND, so only the next typed command is recorded
+_absurdtty_arm() {
+    _absurdtty_armed=1
+    _absurdtty_next=$HISTCMD
+}
+
+trap '_absurdtty_preexec' DEBUG
+PROMPT_COMMAND="_absurdtty_precmd${PROMPT_COMMAND:+; $PROMPT_COMMAND}; _absurdtty_arm"
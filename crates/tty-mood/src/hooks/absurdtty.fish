@@ -0,0 +1,27 @@
+# absurdtty event log hook for fish (3.2 or later).
+# Load it from ~/.config/fish/config.fish with: tty-mood init fish | source
+#
+# Appends one line per command to the event log:
+# start time, duration (ms), exit status, cwd hash, command name.
+# Arguments and directory names are never written.
+
+set -g _absurdtty_log @EVENT_LOG@
+
+function _absurdtty_preexec --on-event fish_preexec
+    # Lines typed with a leading space stay private, as in fish history
+    string match -qr '^\s' -- $argv[1]; and return
+    set -g _absurdtty_start (date +%s)
+    # Exactly one word: the first line of a multi-line command, tabs as spaces
+    set -g _absurdtty_cmd (string split -f1 \n -- $argv[1] | string replace -a \t ' ' | string split -m1 -f1 ' ')
+    if test "$PWD" != "$_absurdtty_pwd"
+        set -g _absurdtty_pwd $PWD
+        set -g _absurdtty_cwd (printf %s $PWD | cksum | string split -f1 ' ')
+    end
+end
+
+function _absurdtty_postexec --on-event fish_postexec
+    set -l exit $status
+    set -q _absurdtty_start[1]; or return
+    printf '%s\t%d\t%d\t%s\t%s\n' $_absurdtty_start $CMD_DURATION $exit $_absurdtty_cwd $_absurdtty_cmd >>$_absurdtty_log
+    set -e _absurdtty_start
+end
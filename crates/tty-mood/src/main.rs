@@ -3,15 +3,33 @@
 //! Analyzes shell history to generate mood signatures.
 
 mod cli;
+mod config;
 mod history;
+mod hook;
 mod mood;
+mod range;
 mod report;
 mod signals;
+mod timezone;
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use cli::{Cli, Commands, OutputFormat};
-use history::HistoryParser;
+use cli::{Cli, Commands, OutputFormat, RangeArgs, SourceArgs};
+use history::{
+    aliases::AliasTable,
+    detect::Detection,
+    dictionary::CommandDictionary,
+    ignore::{IgnoreRules, IgnoreStats, IgnoringParser},
+    projects::ProjectParser,
+    session::Session,
+    HistoryEntry, HistoryFormat, HistoryParser,
+};
+use range::Interval;
+use std::io::Read;
+use std::path::PathBuf;
+use time::macros::format_description;
+use time::{Duration, OffsetDateTime};
+use timezone::LocalZone;
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
@@ -19,54 +37,82 @@ fn main() -> Result<()> {
     match &cli.command {
         Commands::Generate {
             range,
-            history,
+            source,
             out,
             dry_run,
-        } => cmd_generate(&cli, range, history.clone(), out.clone(), *dry_run),
+            full,
+            explain_ignored,
+        } => cmd_generate(
+            &cli,
+            range,
+            source,
+            out.clone(),
+            *dry_run,
+            *full,
+            *explain_ignored,
+        ),
 
         Commands::Show { mood_file } => cmd_show(&cli, mood_file.clone()),
 
-        Commands::Signals {
+        Commands::Signals { range, source, all } => cmd_signals(&cli, range, source, *all),
+
+        Commands::Sessions {
             range,
-            history,
-            all,
-        } => cmd_signals(&cli, range, history.clone(), *all),
+            source,
+            idle,
+        } => cmd_sessions(&cli, range, source, *idle),
+
+        Commands::Init { shell } => cmd_init(*shell),
     }
 }
 
 fn cmd_generate(
     cli: &Cli,
-    range: &str,
-    history_path: Option<std::path::PathBuf>,
-    out_path: Option<std::path::PathBuf>,
+    range: &RangeArgs,
+    source: &SourceArgs,
+    out_path: Option<PathBuf>,
     dry_run: bool,
+    full: bool,
+    explain_ignored: bool,
 ) -> Result<()> {
-    // Resolve history file
-    let history_path = history_path
-        .or_else(absurd_core::Paths::shell_history)
-        .context("Could not detect shell history. Use --history to specify path.")?;
-
-    eprintln!("Reading history from: {:?}", history_path);
-
-    // Parse history
-    let parser = history::ZshHistoryParser::new();
-    let entries = parser.parse_file(&history_path)?;
+    let zone = LocalZone::resolve(cli.tz.as_deref())?;
+    let interval = Interval::resolve(range, &zone, OffsetDateTime::now_utc())?;
+    // Explaining needs the whole history, not just what's new since the cursor
+    let LoadedHistory {
+        entries,
+        previous,
+        sources,
+        aliases,
+        ignored,
+        ignore_rules,
+    } = load_history(source, &interval, &zone, !full && !explain_ignored)?;
+
+    for source in &sources {
+        eprintln!(
+            "Read {} {} entries from: {} ({})",
+            source.entries_read, source.format, source.history_path, source.detection
+        );
+    }
 
-    eprintln!("Found {} history entries", entries.len());
+    if aliases > 0 {
+        eprintln!("Resolving {} aliases and functions", aliases);
+    }
 
-    // Filter by time range
-    let days = cli::parse_range(range)?;
-    let (since, until) = history::last_n_days(days);
-    let entries = history::filter_by_range(entries, Some(since), Some(until));
+    if explain_ignored {
+        eprintln!("Ignored {} entries:", ignored.total());
+        for (reason, count) in ignored.counts() {
+            eprintln!("  {:>6}  {}", count, ignore_rules.describe(*reason));
+        }
+    }
 
-    eprintln!("Analyzing {} entries from last {} days", entries.len(), days);
+    eprintln!("Analyzing {} entries from {}", entries.len(), interval);
 
     if entries.is_empty() {
         anyhow::bail!("No history entries found in the specified time range");
     }
 
-    // Analyze signals
-    let signals = signals::analyze(&entries);
+    // Analyze signals, and how they changed since the window before
+    let signals = signals::analyze_with_previous(&entries, &previous);
 
     eprintln!("Detected {} signals", signals.signals.len());
 
@@ -80,14 +126,14 @@ fn cmd_generate(
     );
 
     // Create report
-    let source = report::SourceInfo {
-        shell: detect_shell(),
-        history_path: history_path.to_string_lossy().to_string(),
-        read_only: true,
-        entries_analyzed: entries.len(),
-    };
-
-    let signature = report::MoodSignature::new(&detected_mood, &signals, source, range, cli.seed);
+    let signature = report::MoodSignature::new(
+        &detected_mood,
+        &signals,
+        sources,
+        &interval.iso8601(),
+        cli.seed,
+    )
+    .with_timezone(&zone);
 
     // Output
     if dry_run {
@@ -112,7 +158,7 @@ fn cmd_generate(
     Ok(())
 }
 
-fn cmd_show(cli: &Cli, mood_file: Option<std::path::PathBuf>) -> Result<()> {
+fn cmd_show(cli: &Cli, mood_file: Option<PathBuf>) -> Result<()> {
     let mood_file = mood_file
         .or_else(|| absurd_core::Paths::mood_file().ok())
         .context("Could not determine mood file path")?;
@@ -138,24 +184,12 @@ fn cmd_show(cli: &Cli, mood_file: Option<std::path::PathBuf>) -> Result<()> {
     Ok(())
 }
 
-fn cmd_signals(
-    cli: &Cli,
-    range: &str,
-    history_path: Option<std::path::PathBuf>,
-    show_all: bool,
-) -> Result<()> {
-    // Resolve history file
-    let history_path = history_path
-        .or_else(absurd_core::Paths::shell_history)
-        .context("Could not detect shell history")?;
-
-    // Parse and filter history
-    let parser = history::ZshHistoryParser::new();
-    let entries = parser.parse_file(&history_path)?;
-
-    let days = cli::parse_range(range)?;
-    let (since, until) = history::last_n_days(days);
-    let entries = history::filter_by_range(entries, Some(since), Some(until));
+fn cmd_signals(cli: &Cli, range: &RangeArgs, source: &SourceArgs, show_all: bool) -> Result<()> {
+    let zone = LocalZone::resolve(cli.tz.as_deref())?;
+    let interval = Interval::resolve(range, &zone, OffsetDateTime::now_utc())?;
+    let LoadedHistory {
+        entries, previous, ..
+    } = load_history(source, &interval, &zone, true)?;
 
     if entries.is_empty() {
         println!("No history entries found in the specified time range.");
@@ -163,7 +197,7 @@ fn cmd_signals(
     }
 
     // Analyze
-    let signals = signals::analyze(&entries);
+    let signals = signals::analyze_with_previous(&entries, &previous);
 
     // Filter and sort
     let mut signals_vec: Vec<_> = signals
@@ -180,7 +214,12 @@ fn cmd_signals(
             println!("{}", json);
         }
         OutputFormat::Text => {
-            println!("DETECTED SIGNALS (last {} days, {} entries):\n", days, entries.len());
+            println!(
+                "DETECTED SIGNALS ({}, {} entries, {}):\n",
+                interval,
+                entries.len(),
+                zone.name()
+            );
 
             if signals_vec.is_empty() {
                 println!("  No significant signals detected.");
@@ -204,16 +243,305 @@ fn cmd_signals(
                     println!();
                 }
             }
+
+            for unavailable in &signals.unavailable {
+                println!("  {:24} unavailable: {}", unavailable.family, unavailable.reason);
+            }
         }
     }
 
     Ok(())
 }
 
-/// Detect current shell from environment.
-fn detect_shell() -> String {
-    std::env::var("SHELL")
-        .ok()
-        .and_then(|s| s.rsplit('/').next().map(String::from))
-        .unwrap_or_else(|| "unknown".to_string())
+fn cmd_sessions(cli: &Cli, range: &RangeArgs, source: &SourceArgs, idle: u32) -> Result<()> {
+    let zone = LocalZone::resolve(cli.tz.as_deref())?;
+    let interval = Interval::resolve(range, &zone, OffsetDateTime::now_utc())?;
+    let LoadedHistory { entries, .. } = load_history(source, &interval, &zone, true)?;
+
+    if entries.is_empty() {
+        println!("No history entries found in the specified time range.");
+        return Ok(());
+    }
+
+    let sessions: Vec<(Session, absurd_lexicon::moods::Mood)> =
+        history::session::segment(entries, Duration::minutes(i64::from(idle)))
+            .into_iter()
+            .map(|session| {
+                let mood = mood::detect_mood(&signals::analyze(&session.entries));
+                (session, mood)
+            })
+            .collect();
+
+    match cli.format {
+        OutputFormat::Json => {
+            let infos: Vec<report::SessionInfo> = sessions
+                .iter()
+                .map(|(session, mood)| report::SessionInfo {
+                    start: session.start(),
+                    end: session.end(),
+                    entries: session.entries.len(),
+                    mood: report::MoodInfo::from_mood(mood),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&infos)?);
+        }
+        OutputFormat::Text => {
+            println!(
+                "SESSIONS ({}, {} idle minutes, {}):\n",
+                interval,
+                idle,
+                zone.name()
+            );
+
+            for (session, mood) in &sessions {
+                let (start, end) = session_span(session);
+                println!(
+                    "  {:20} {:16} {:>6}  {:24} {:3.0}%",
+                    start,
+                    end,
+                    session.entries.len(),
+                    mood.label(),
+                    mood.confidence * 100.0
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Start and end of a session for display; the end date is left out
+/// when the session didn't cross midnight.
+fn session_span(session: &Session) -> (String, String) {
+    let full = format_description!("[weekday repr:short] [year]-[month]-[day] [hour]:[minute]");
+    let clock = format_description!("[hour]:[minute]");
+
+    match (session.start(), session.end()) {
+        (Some(start), Some(end)) => {
+            let end_format = if end.date() == start.date() { clock } else { full };
+            (
+                start.format(full).unwrap_or_default(),
+                format!("to {}", end.format(end_format).unwrap_or_default()),
+            )
+        }
+        _ => ("(untimed)".to_string(), "-".to_string()),
+    }
+}
+
+fn cmd_init(shell: hook::HookShell) -> Result<()> {
+    let log = absurd_core::Paths::event_log()?;
+    hook::ensure_event_log(&log)?;
+    print!("{}", shell.script(&log));
+    Ok(())
+}
+
+/// History from every source, merged and filtered to the analysis range.
+struct LoadedHistory {
+    entries: Vec<HistoryEntry>,
+    /// Entries from the window of equal length before the range; empty
+    /// when history doesn't reach back that far
+    previous: Vec<HistoryEntry>,
+    sources: Vec<report::SourceInfo>,
+    /// Number of aliases and functions resolved
+    aliases: usize,
+    /// Entries dropped by ignore rules
+    ignored: IgnoreStats,
+    ignore_rules: IgnoreRules,
+}
+
+/// Resolve, parse, merge, alias-resolve and range-filter all history sources.
+///
+/// Entries from the window before the range are kept aside for trends.
+///
+/// With `incremental`, append-only formats are read from their saved
+/// cursor so only new lines are parsed.
+fn load_history(
+    source: &SourceArgs,
+    interval: &Interval,
+    zone: &LocalZone,
+    incremental: bool,
+) -> Result<LoadedHistory> {
+    let config = config::Config::load()?;
+    let detections = resolve_sources(&config, &source.history, source.history_format.fixed())?;
+
+    // The hook's event log annotates shell history rather than adding
+    // to it; it is only analyzed on its own when nothing else is given
+    let (logs, detections): (Vec<_>, Vec<_>) = detections
+        .into_iter()
+        .partition(|d| d.format == HistoryFormat::Events && !d.is_stdin());
+    let (mut detections, logs) = if detections.is_empty() {
+        (logs, Vec::new())
+    } else {
+        let mut logs: Vec<PathBuf> = logs.into_iter().map(|d| d.path).collect();
+        let default_log = absurd_core::Paths::event_log()?;
+        if logs.is_empty() && default_log.is_file() {
+            logs.push(default_log);
+        }
+        (detections, logs)
+    };
+    let mut events = Vec::new();
+    for log in &logs {
+        events.extend(history::EventLogParser::new()
+                .preserve_full_lines(true)
+                .parse_file(log)?);
+    }
+
+    let reader = history::cursor::IncrementalReader::new()?;
+    let rules = config.ignore.rules()?;
+    let mut ignored = IgnoreStats::default();
+
+    let mut parsed = Vec::with_capacity(detections.len());
+    for detection in &mut detections {
+        // Full lines stay in memory for deduplication and ignore rules;
+        // they are never written. Projects are resolved before ignore
+        // rules run, so ignored `cd`s still move the working directory
+        let parser = IgnoringParser::new(
+            Box::new(ProjectParser::new(detection.format.parser(true))),
+            &rules,
+        );
+
+        if detection.is_stdin() {
+            let mut bytes = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut bytes)
+                .context("Failed to read history from stdin")?;
+            if bytes.starts_with(history::detect::SQLITE_HEADER) {
+                anyhow::bail!("SQLite history can't be read from stdin; pass the database path with --history");
+            }
+            parsed.push(parser.parse_bytes(&bytes)?);
+        } else if incremental && detection.format.supports_incremental() {
+            let read = reader.read(detection, &parser)?;
+            detection.reason = if read.full_read {
+                format!("{}; cursor reset, full read", detection.reason)
+            } else {
+                format!("{}; cursor: {} new entries", detection.reason, read.parsed)
+            };
+            parsed.push(read.entries);
+        } else {
+            parsed.push(parser.parse_file(&detection.path)?);
+        }
+        ignored.merge(&parser.stats());
+
+        let annotated = history::events::annotate(parsed.last_mut().unwrap(), &events);
+        if annotated > 0 {
+            detection.reason = format!(
+                "{}; exit status and duration of {} entries from the event log",
+                detection.reason, annotated
+            );
+        }
+    }
+    let read_counts: Vec<usize> = parsed.iter().map(Vec::len).collect();
+
+    let mut entries = history::merge::merge(parsed);
+
+    // Parsers read timestamps as UTC; temporal signals need wall-clock hours
+    for entry in &mut entries {
+        entry.timestamp = entry.timestamp.map(|ts| zone.to_local(ts));
+    }
+
+    // `--aliases` wins over configured alias files, which win over rc files
+    let alias_table = if !source.aliases.is_empty() {
+        AliasTable::load(&source.aliases)?
+    } else if !config.aliases.is_empty() {
+        AliasTable::load(&config.alias_paths())?
+    } else {
+        AliasTable::discover()
+    };
+    alias_table.resolve_all(&mut entries);
+
+    let mut dictionary = CommandDictionary::load()?;
+    dictionary.extend(alias_table.names());
+    dictionary.check_all(&mut entries);
+
+    // A window only partly covered by history would look like a quiet one
+    let previous = match interval.preceding() {
+        Some(preceding)
+            if entries
+                .iter()
+                .any(|e| e.timestamp.is_some_and(|ts| ts <= preceding.since)) =>
+        {
+            entries
+                .iter()
+                .filter(|e| {
+                    e.timestamp
+                        .is_some_and(|ts| ts >= preceding.since && ts < preceding.until)
+                })
+                .cloned()
+                .collect()
+        }
+        _ => Vec::new(),
+    };
+
+    let entries = history::filter_by_range(entries, Some(interval.since), Some(interval.until));
+
+    let sources = detections
+        .into_iter()
+        .enumerate()
+        .map(|(index, detection)| report::SourceInfo {
+            shell: detection.format.shell().to_string(),
+            history_path: detection.display_path(),
+            format: detection.format.name().to_string(),
+            detection: detection.reason,
+            read_only: true,
+            entries_read: read_counts[index],
+            entries_analyzed: entries.iter().filter(|e| e.source == index).count(),
+        })
+        .collect();
+
+    Ok(LoadedHistory {
+        entries,
+        previous,
+        sources,
+        aliases: alias_table.len(),
+        ignored,
+        ignore_rules: rules,
+    })
+}
+
+/// Decide which history sources to read.
+///
+/// `--history` paths win over configured sources, which win over detection.
+fn resolve_sources(
+    config: &config::Config,
+    history_paths: &[PathBuf],
+    history_format: Option<HistoryFormat>,
+) -> Result<Vec<Detection>> {
+    if !history_paths.is_empty() {
+        let stdin_count = history_paths
+            .iter()
+            .filter(|p| p.as_os_str() == history::detect::STDIN)
+            .count();
+        if stdin_count > 1 {
+            anyhow::bail!("--history - can only be given once");
+        }
+
+        return history_paths
+            .iter()
+            .map(|path| history::detect::resolve(Some(path.clone()), history_format))
+            .collect();
+    }
+
+    if !config.history.is_empty() {
+        return config
+            .history
+            .iter()
+            .map(|source| {
+                let path = source.path();
+                let (format, reason) = match source.format()?.or(history_format) {
+                    Some(format) => (format, "configured source".to_string()),
+                    None => {
+                        let (format, why) = history::detect::identify(&path);
+                        (format, format!("configured source; {}", why))
+                    }
+                };
+                Ok(Detection {
+                    path,
+                    format,
+                    reason,
+                })
+            })
+            .collect();
+    }
+
+    Ok(vec![history::detect::resolve(None, history_format)?])
 }
@@ -44,25 +44,41 @@ pub fn detect_mood(signals: &SignalCollection) -> Mood {
 
 /// Score for feral_productivity mood.
 ///
-/// High cadence + high diversity + late night activity
+/// High cadence + high diversity + late night activity + nothing slow
+/// + elaborate one-liners + picking up pace + working for hours on end
 fn score_feral_productivity(signals: &SignalCollection) -> f64 {
     let cadence_high = signals.score("cadence_high");
     let diversity_high = signals.score("command_diversity_high");
     let late_night = signals.score("late_night_orbit");
     let burst = signals.score("burst_pattern");
+    let instant = signals.score("instant_gratification");
 
     // Weighted combination
     let base = cadence_high * 0.35 + diversity_high * 0.25 + late_night * 0.2 + burst * 0.2;
 
+    // Never waiting on anything keeps the pace up
+    let instant_bonus = instant * 0.15;
+
+    // Writing whole programs at the prompt
+    let baroque_bonus = signals.score("pipeline_baroque") * 0.15;
+
+    // More commands than last time: the pace is still building
+    let momentum_bonus = signals.score("cadence_rising") * 0.15;
+
+    // Hours at the prompt without looking up
+    let marathon_bonus = signals.score("marathon_streak") * 0.1;
+
     // Penalty for too many errors (feral but not sloppy)
     let typo_penalty = signals.score("typo_rate_high") * 0.3;
 
-    (base - typo_penalty).max(0.0)
+    (base + instant_bonus + baroque_bonus + momentum_bonus + marathon_bonus - typo_penalty)
+        .clamp(0.0, 1.0)
 }
 
 /// Score for exhausted mood.
 ///
-/// Low cadence + high typo rate + repeated commands
+/// Low cadence + high typo rate + repeated commands + too little rest,
+/// and getting worse
 fn score_exhausted(signals: &SignalCollection) -> f64 {
     let cadence_low = signals.score("cadence_low");
     let typo_high = signals.score("typo_rate_high");
@@ -72,75 +88,131 @@ fn score_exhausted(signals: &SignalCollection) -> f64 {
 
     // Exhaustion indicators
     let typo_score = typo_high * 0.8 + typo_medium * 0.4;
-
-    cadence_low * 0.3 + typo_score * 0.3 + repeats * 0.2 + late_night * 0.2
+    let level = cadence_low * 0.3 + typo_score * 0.3 + repeats * 0.2 + late_night * 0.2;
+
+    // Rest: short nights and long stretches without a break add up
+    let rest_deficit = signals.score("sleep_debt") * 0.3
+        + signals.score("marathon_streak") * 0.15
+        + signals.score("irregular_schedule") * 0.1;
+
+    // Direction: nights getting later and typos more frequent wear on,
+    // a recovering schedule eases off
+    let worsening = signals.score("night_shift_creeping") * 0.2
+        + signals.score("typo_rate_rising") * 0.15
+        + signals.score("cadence_falling") * 0.1;
+    let recovering =
+        signals.score("night_shift_receding") * 0.1 + signals.score("typo_rate_falling") * 0.1;
+
+    (level + rest_deficit + worsening - recovering).clamp(0.0, 1.0)
 }
 
 /// Score for methodical mood.
 ///
-/// Steady rhythm + low error rate + systematic patterns
+/// Steady rhythm + low error rate + systematic patterns + patient builds
+/// + staying in one project + recurring command rituals + fewer typos
 fn score_methodical(signals: &SignalCollection) -> f64 {
     let rhythm = signals.score("steady_rhythm");
     let typo_low = signals.score("typo_rate_low");
     let build_cycle = signals.score("build_cycle");
     let git_heavy = signals.score("git_heavy");
+    let vigil = signals.score("long_running_vigil");
+    let failure_low = signals.score("failure_rate_low");
+    let dwell = signals.score("deep_dwell");
+    let ritual = signals.score("ritual_sequence");
 
     // Bonus for consistent tool usage
     let workflow_bonus = (build_cycle + git_heavy) * 0.5;
 
+    // The same steps in the same order (fmt, clippy, test)
+    let ritual_bonus = ritual * 0.1;
+
+    // Getting more careful than last time
+    let improving_bonus = signals.score("typo_rate_falling") * 0.1;
+
+    // Commands that actually succeed (sources that record exit status)
+    let success_bonus = failure_low * 0.1;
+
+    // Letting long builds finish is a methodical habit
+    let patience_bonus = vigil * 0.1;
+
+    // Long stretches inside one project
+    let focus_bonus = dwell * 0.15;
+
     // Penalty for chaos indicators
     let chaos_penalty = signals.score("burst_pattern") * 0.2
-        + signals.score("context_switching") * 0.2;
-
-    (rhythm * 0.4 + typo_low * 0.2 + workflow_bonus * 0.2 - chaos_penalty).max(0.0)
+        + signals.score("context_switching") * 0.2
+        + signals.score("project_hopping") * 0.1
+        + signals.score("irregular_schedule") * 0.1
+        + signals.score("impatience") * 0.2;
+
+    (rhythm * 0.4 + typo_low * 0.2 + workflow_bonus * 0.2 + patience_bonus + success_bonus
+        + focus_bonus
+        + ritual_bonus
+        + improving_bonus
+        - chaos_penalty)
+        .clamp(0.0, 1.0)
 }
 
 /// Score for chaotic_neutral mood.
 ///
-/// High diversity + burst patterns + unpredictable timing
+/// High diversity + burst patterns + unpredictable timing + project hopping
+/// + no fixed hours
 fn score_chaotic_neutral(signals: &SignalCollection) -> f64 {
     let diversity = signals.score("command_diversity_high");
     let burst = signals.score("burst_pattern");
     let context_switch = signals.score("context_switching");
     let time_spread = signals.score("time_spread");
+    let impatience = signals.score("impatience");
+    let hopping = signals.score("project_hopping");
 
     // Chaos without complete disaster
-    let base = diversity * 0.3 + burst * 0.25 + context_switch * 0.25 + time_spread * 0.2;
+    let base = diversity * 0.3 + burst * 0.25 + context_switch * 0.25 + time_spread * 0.2
+        + impatience * 0.15
+        + hopping * 0.2
+        + signals.score("irregular_schedule") * 0.15;
 
     // Penalty for too orderly
     let order_penalty = signals.score("steady_rhythm") * 0.3;
 
-    (base - order_penalty).max(0.0)
+    (base - order_penalty).clamp(0.0, 1.0)
 }
 
 /// Score for bureaucratic_zen mood.
 ///
-/// Steady patterns + form-like sequences + git operations
+/// Steady patterns + form-like sequences + git operations + plain commands
 fn score_bureaucratic_zen(signals: &SignalCollection) -> f64 {
     let rhythm = signals.score("steady_rhythm");
     let git_heavy = signals.score("git_heavy");
     let typo_low = signals.score("typo_rate_low");
     let weekday = signals.score("weekday_bound");
+    let minimalist = signals.score("minimalist_invocation");
+    let ritual = signals.score("ritual_sequence");
 
-    // Bureaucratic: orderly, git-focused, working hours
-    let base = rhythm * 0.3 + git_heavy * 0.3 + typo_low * 0.2 + weekday * 0.2;
+    // Bureaucratic: orderly, git-focused, working hours, one form at a time,
+    // the same forms filled in the same order
+    let base = rhythm * 0.3 + git_heavy * 0.3 + typo_low * 0.2 + weekday * 0.2 + minimalist * 0.1
+        + ritual * 0.25;
 
     // Penalty for chaos
     let chaos_penalty = signals.score("late_night_orbit") * 0.2;
 
-    (base - chaos_penalty).max(0.0)
+    (base - chaos_penalty).clamp(0.0, 1.0)
 }
 
 /// Score for ambient_drift mood.
 ///
-/// Low diversity + low cadence + no clear direction
+/// Low diversity + low cadence + no clear direction + waiting on things
 fn score_ambient_drift(signals: &SignalCollection) -> f64 {
     let diversity_low = signals.score("command_diversity_low");
     let cadence_low = signals.score("cadence_low");
     let status_checks = signals.score("status_check_loop");
+    let vigil = signals.score("long_running_vigil");
 
     // Drift: present but unfocused
-    diversity_low * 0.35 + cadence_low * 0.35 + status_checks * 0.3
+    let base = diversity_low * 0.35 + cadence_low * 0.35 + status_checks * 0.3;
+
+    // Watching a build scroll by, or slowly winding down
+    (base + vigil * 0.15 + signals.score("cadence_falling") * 0.1).clamp(0.0, 1.0)
 }
 
 /// Score for recursive_doubt mood.
@@ -162,7 +234,7 @@ fn score_recursive_doubt(signals: &SignalCollection) -> f64 {
         0.0
     };
 
-    (base + git_bonus + git_heavy * 0.1).min(1.0)
+    (base + git_bonus + git_heavy * 0.1).clamp(0.0, 1.0)
 }
 
 /// Score for emergency_mode mood.
@@ -173,20 +245,35 @@ fn score_emergency_mode(signals: &SignalCollection) -> f64 {
     let typo_high = signals.score("typo_rate_high");
     let corrections = signals.score("correction_pattern");
     let cadence_high = signals.score("cadence_high");
+    let failures = signals.score("failure_rate_high");
+    let impatience = signals.score("impatience");
 
     // Emergency: fast, frantic, error-prone
     let base = burst * 0.3 + typo_high * 0.3 + corrections * 0.2 + cadence_high * 0.2;
 
+    // Real failures only exist for sources that record exit status
+    let failure_bonus = failures * 0.2;
+
+    // Killing commands before they can finish
+    let impatience_bonus = impatience * 0.1;
+
     // Must have multiple indicators to count as emergency
-    let indicator_count = [burst > 0.3, typo_high > 0.3, corrections > 0.2, cadence_high > 0.5]
-        .iter()
-        .filter(|&&x| x)
-        .count();
+    let indicator_count = [
+        burst > 0.3,
+        typo_high > 0.3,
+        corrections > 0.2,
+        cadence_high > 0.5,
+        failures > 0.5,
+        impatience > 0.5,
+    ]
+    .iter()
+    .filter(|&&x| x)
+    .count();
 
     if indicator_count < 2 {
         0.0
     } else {
-        base
+        (base + failure_bonus + impatience_bonus).clamp(0.0, 1.0)
     }
 }
 
@@ -203,6 +290,115 @@ mod tests {
         signals
     }
 
+    #[test]
+    fn saturated_moods_stay_within_one() {
+        type Scorer = fn(&SignalCollection) -> f64;
+        let cases: [(Scorer, &[&str]); 8] = [
+            (
+                score_feral_productivity,
+                &[
+                    "cadence_high",
+                    "command_diversity_high",
+                    "late_night_orbit",
+                    "burst_pattern",
+                    "instant_gratification",
+                    "pipeline_baroque",
+                    "cadence_rising",
+                    "marathon_streak",
+                ],
+            ),
+            (
+                score_exhausted,
+                &[
+                    "cadence_low",
+                    "typo_rate_high",
+                    "typo_rate_medium",
+                    "repeat_commands",
+                    "late_night_orbit",
+                    "sleep_debt",
+                    "marathon_streak",
+                    "irregular_schedule",
+                    "night_shift_creeping",
+                    "typo_rate_rising",
+                    "cadence_falling",
+                ],
+            ),
+            (
+                score_methodical,
+                &[
+                    "steady_rhythm",
+                    "typo_rate_low",
+                    "build_cycle",
+                    "git_heavy",
+                    "long_running_vigil",
+                    "failure_rate_low",
+                    "deep_dwell",
+                    "ritual_sequence",
+                    "typo_rate_falling",
+                ],
+            ),
+            (
+                score_chaotic_neutral,
+                &[
+                    "command_diversity_high",
+                    "burst_pattern",
+                    "context_switching",
+                    "time_spread",
+                    "impatience",
+                    "project_hopping",
+                    "irregular_schedule",
+                ],
+            ),
+            (
+                score_bureaucratic_zen,
+                &[
+                    "steady_rhythm",
+                    "git_heavy",
+                    "typo_rate_low",
+                    "weekday_bound",
+                    "minimalist_invocation",
+                    "ritual_sequence",
+                ],
+            ),
+            (
+                score_ambient_drift,
+                &[
+                    "command_diversity_low",
+                    "cadence_low",
+                    "status_check_loop",
+                    "long_running_vigil",
+                    "cadence_falling",
+                ],
+            ),
+            (
+                score_recursive_doubt,
+                &[
+                    "status_check_loop",
+                    "repeat_commands",
+                    "correction_pattern",
+                    "git_heavy",
+                ],
+            ),
+            (
+                score_emergency_mode,
+                &[
+                    "burst_pattern",
+                    "typo_rate_high",
+                    "correction_pattern",
+                    "cadence_high",
+                    "failure_rate_high",
+                    "impatience",
+                ],
+            ),
+        ];
+
+        for (score, ids) in cases {
+            let saturated: Vec<_> = ids.iter().map(|id| (*id, 1.0)).collect();
+            let signals = signals_with(&saturated);
+            assert!(score(&signals) <= 1.0, "{:?}", ids);
+        }
+    }
+
     #[test]
     fn feral_productivity_detected() {
         let signals = signals_with(&[
@@ -228,6 +424,18 @@ mod tests {
         assert_eq!(mood.id, MoodId::Exhausted);
     }
 
+    #[test]
+    fn emergency_mode_from_real_failures() {
+        let signals = signals_with(&[
+            ("burst_pattern", 0.8),
+            ("failure_rate_high", 0.9),
+            ("cadence_high", 0.6),
+        ]);
+
+        let mood = detect_mood(&signals);
+        assert_eq!(mood.id, MoodId::EmergencyMode);
+    }
+
     #[test]
     fn neutral_when_no_signals() {
         let signals = SignalCollection::new();
@@ -246,5 +454,95 @@ mod tests {
         let mood = detect_mood(&signals);
         assert_eq!(mood.id, MoodId::RecursiveDoubt);
     }
+
+    #[test]
+    fn impatience_counts_toward_emergency() {
+        let frantic = signals_with(&[("burst_pattern", 0.6), ("impatience", 0.9)]);
+        assert!(score_emergency_mode(&frantic) > 0.0);
+
+        let bursty = signals_with(&[("burst_pattern", 0.6)]);
+        assert_eq!(score_emergency_mode(&bursty), 0.0);
+    }
+
+    #[test]
+    fn long_builds_favor_methodical_over_impatience() {
+        let patient = signals_with(&[("steady_rhythm", 0.7), ("long_running_vigil", 0.8)]);
+        let impatient = signals_with(&[("steady_rhythm", 0.7), ("impatience", 0.8)]);
+
+        assert!(score_methodical(&patient) > score_methodical(&impatient));
+        assert!(score_ambient_drift(&patient) > 0.0);
+    }
+
+    #[test]
+    fn project_hopping_is_chaotic_and_dwelling_methodical() {
+        let hopping = signals_with(&[("steady_rhythm", 0.5), ("project_hopping", 0.9)]);
+        let dwelling = signals_with(&[("steady_rhythm", 0.5), ("deep_dwell", 0.9)]);
+
+        assert!(score_chaotic_neutral(&hopping) > score_chaotic_neutral(&dwelling));
+        assert!(score_methodical(&dwelling) > score_methodical(&hopping));
+    }
+
+    #[test]
+    fn rituals_are_bureaucratic() {
+        let plain = signals_with(&[("steady_rhythm", 0.6), ("git_heavy", 0.6)]);
+        let ritual = signals_with(&[
+            ("steady_rhythm", 0.6),
+            ("git_heavy", 0.6),
+            ("ritual_sequence", 0.9),
+        ]);
+
+        assert!(score_bureaucratic_zen(&ritual) > score_bureaucratic_zen(&plain));
+        assert!(score_methodical(&ritual) > score_methodical(&plain));
+    }
+
+    #[test]
+    fn direction_weighs_with_level() {
+        let level = [("typo_rate_medium", 0.5), ("late_night_orbit", 0.4)];
+        let steady = signals_with(&level);
+        let worsening = signals_with(&[
+            level[0],
+            level[1],
+            ("night_shift_creeping", 0.8),
+            ("typo_rate_rising", 0.6),
+        ]);
+        let recovering = signals_with(&[
+            level[0],
+            level[1],
+            ("night_shift_receding", 0.8),
+            ("typo_rate_falling", 0.6),
+        ]);
+
+        assert!(score_exhausted(&worsening) > score_exhausted(&steady));
+        assert!(score_exhausted(&recovering) < score_exhausted(&steady));
+        assert!(score_methodical(&recovering) > score_methodical(&steady));
+    }
+
+    #[test]
+    fn missing_rest_is_exhausting() {
+        let signals = signals_with(&[
+            ("sleep_debt", 1.0),
+            ("marathon_streak", 0.8),
+            ("typo_rate_medium", 0.5),
+            ("late_night_orbit", 0.6),
+        ]);
+
+        let mood = detect_mood(&signals);
+        assert_eq!(mood.id, MoodId::Exhausted);
+
+        // Every exhaustion signal at full strength still scores 1.0
+        let signals = signals_with(&[
+            ("cadence_low", 1.0),
+            ("typo_rate_high", 1.0),
+            ("repeat_commands", 1.0),
+            ("late_night_orbit", 1.0),
+            ("sleep_debt", 1.0),
+            ("marathon_streak", 1.0),
+            ("irregular_schedule", 1.0),
+            ("night_shift_creeping", 1.0),
+            ("typo_rate_rising", 1.0),
+            ("cadence_falling", 1.0),
+        ]);
+        assert_eq!(score_exhausted(&signals), 1.0);
+    }
 }
 
@@ -0,0 +1,277 @@
+//! Analysis time ranges.
+//!
+//! Turns `--range`, `--since` and `--until` into a concrete interval:
+//!
+//! - `--since` (with an optional `--until`) gives an absolute range
+//! - relative ranges (`12h`, `7d`) count back from `--until`, or now
+//! - named ranges (`today`, `this-week`, ...) follow the local calendar,
+//!   so `today` starts at local midnight
+//!
+//! Dates and times are read in the analysis time zone.
+
+use crate::cli::{RangeArgs, RangeSpec};
+use crate::timezone::LocalZone;
+use anyhow::{Context, Result};
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+/// A resolved time range, in the analysis time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interval {
+    pub since: OffsetDateTime,
+    pub until: OffsetDateTime,
+}
+
+impl Interval {
+    /// Resolve range arguments relative to `now`.
+    pub fn resolve(args: &RangeArgs, zone: &LocalZone, now: OffsetDateTime) -> Result<Self> {
+        let now = zone.to_local(now).replace_nanosecond(0)?;
+        let since = args
+            .since
+            .as_deref()
+            .map(|value| parse_bound(value, zone, false))
+            .transpose()
+            .context("Invalid --since")?;
+        let until = args
+            .until
+            .as_deref()
+            .map(|value| parse_bound(value, zone, true))
+            .transpose()
+            .context("Invalid --until")?;
+
+        let interval = match (since, until) {
+            (Some(since), until) => Self {
+                since,
+                until: until.unwrap_or(now),
+            },
+            (None, Some(_)) if args.range.is_named() => {
+                anyhow::bail!("--until can't be combined with --range {}", args.range)
+            }
+            (None, until) => Self::relative(args.range, zone, until.unwrap_or(now))?,
+        };
+
+        if interval.since >= interval.until {
+            anyhow::bail!(
+                "Empty time range: {} is not before {}",
+                interval.since,
+                interval.until
+            );
+        }
+
+        Ok(interval)
+    }
+
+    /// A relative or named range ending at `end`.
+    fn relative(spec: RangeSpec, zone: &LocalZone, end: OffsetDateTime) -> Result<Self> {
+        let midnight = |date: Date| zone.instant_at(date.midnight());
+        let today = end.date();
+
+        let back = |span: Duration| {
+            end.checked_sub(span)
+                .with_context(|| format!("Invalid range: {} reaches before the calendar", spec))
+        };
+
+        let (since, until) = match spec {
+            RangeSpec::Hours(hours) => (back(Duration::hours(i64::from(hours)))?, end),
+            RangeSpec::Days(days) => (back(Duration::days(i64::from(days)))?, end),
+            RangeSpec::Today => (midnight(today), end),
+            RangeSpec::Yesterday => (
+                midnight(today.previous_day().context("Date out of range")?),
+                midnight(today),
+            ),
+            RangeSpec::ThisWeek => {
+                let monday =
+                    today - Duration::days(i64::from(today.weekday().number_days_from_monday()));
+                (midnight(monday), end)
+            }
+            RangeSpec::LastMonth => {
+                let this_month = Date::from_calendar_date(today.year(), today.month(), 1)?;
+                let year = if today.month() == Month::January {
+                    today.year() - 1
+                } else {
+                    today.year()
+                };
+                let last_month = Date::from_calendar_date(year, today.month().previous(), 1)?;
+                (midnight(last_month), midnight(this_month))
+            }
+        };
+
+        Ok(Self {
+            since: zone.to_local(since),
+            until: zone.to_local(until),
+        })
+    }
+
+    /// The window of equal length ending where this one starts.
+    ///
+    /// None when it would begin before the calendar does.
+    pub fn preceding(&self) -> Option<Self> {
+        let since = self.since.checked_sub(self.until - self.since)?;
+        Some(Self {
+            since,
+            until: self.since,
+        })
+    }
+
+    /// ISO 8601 interval (`start/end`), as recorded in the signature.
+    pub fn iso8601(&self) -> String {
+        let format = |t: OffsetDateTime| t.format(&Rfc3339).unwrap_or_else(|_| t.to_string());
+        format!("{}/{}", format(self.since), format(self.until))
+    }
+}
+
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = format_description!("[year]-[month]-[day] [hour]:[minute]");
+        let since = self.since.format(&format).map_err(|_| std::fmt::Error)?;
+        let until = self.until.format(&format).map_err(|_| std::fmt::Error)?;
+        write!(f, "{} to {}", since, until)
+    }
+}
+
+/// Parse `YYYY-MM-DD` or `YYYY-MM-DDTHH:MM[:SS]` as local time.
+///
+/// A bare date as the end of a range includes that whole day.
+fn parse_bound(value: &str, zone: &LocalZone, end: bool) -> Result<OffsetDateTime> {
+    let value = value.trim();
+    let (date, time) = match value.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+
+    let date = Date::parse(date, format_description!("[year]-[month]-[day]"))
+        .with_context(|| format!("Expected YYYY-MM-DD, got {}", value))?;
+
+    let local = match time {
+        Some(time) => PrimitiveDateTime::new(date, parse_time(time)?),
+        None if end => date.next_day().context("Date out of range")?.midnight(),
+        None => date.midnight(),
+    };
+
+    Ok(zone.to_local(zone.instant_at(local)))
+}
+
+/// Parse `HH:MM` or `HH:MM:SS`.
+fn parse_time(value: &str) -> Result<Time> {
+    Time::parse(value, format_description!("[hour]:[minute]:[second]"))
+        .or_else(|_| Time::parse(value, format_description!("[hour]:[minute]")))
+        .with_context(|| format!("Expected HH:MM, got {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn zone() -> LocalZone {
+        LocalZone::named("CET-1CEST,M3.5.0,M10.5.0/3").unwrap()
+    }
+
+    fn args(range: &str, since: Option<&str>, until: Option<&str>) -> RangeArgs {
+        RangeArgs {
+            range: range.parse().unwrap(),
+            since: since.map(String::from),
+            until: until.map(String::from),
+        }
+    }
+
+    /// Wednesday 2025-12-10, 15:30 in Berlin.
+    const NOW: OffsetDateTime = datetime!(2025-12-10 14:30 UTC);
+
+    fn resolve(range: &str, since: Option<&str>, until: Option<&str>) -> Result<Interval> {
+        Interval::resolve(&args(range, since, until), &zone(), NOW)
+    }
+
+    #[test]
+    fn relative_ranges_count_back_from_now() {
+        let interval = resolve("12h", None, None).unwrap();
+        assert_eq!(interval.since, datetime!(2025-12-10 03:30 +1));
+        assert_eq!(interval.until, datetime!(2025-12-10 15:30 +1));
+
+        let interval = resolve("7d", None, None).unwrap();
+        assert_eq!(interval.since, datetime!(2025-12-03 15:30 +1));
+    }
+
+    #[test]
+    fn preceding_window_has_equal_length() {
+        let interval = resolve("7d", None, None).unwrap();
+        let previous = interval.preceding().unwrap();
+        assert_eq!(previous.since, datetime!(2025-11-26 15:30 +1));
+        assert_eq!(previous.until, interval.since);
+
+        let yesterday = resolve("yesterday", None, None).unwrap().preceding();
+        assert_eq!(yesterday.unwrap().since, datetime!(2025-12-08 00:00 +1));
+
+        let long = resolve("3000000d", None, None).unwrap();
+        assert!(long.preceding().is_none());
+    }
+
+    #[test]
+    fn named_ranges_follow_the_local_calendar() {
+        let today = resolve("today", None, None).unwrap();
+        assert_eq!(today.since, datetime!(2025-12-10 00:00 +1));
+
+        let yesterday = resolve("yesterday", None, None).unwrap();
+        assert_eq!(yesterday.since, datetime!(2025-12-09 00:00 +1));
+        assert_eq!(yesterday.until, datetime!(2025-12-10 00:00 +1));
+
+        let week = resolve("this-week", None, None).unwrap();
+        assert_eq!(week.since, datetime!(2025-12-08 00:00 +1));
+
+        let month = resolve("last-month", None, None).unwrap();
+        assert_eq!(month.since, datetime!(2025-11-01 00:00 +1));
+        assert_eq!(month.until, datetime!(2025-12-01 00:00 +1));
+    }
+
+    #[test]
+    fn last_month_crosses_dst_and_years() {
+        let zone = zone();
+        let args = args("last-month", None, None);
+
+        let month = Interval::resolve(&args, &zone, datetime!(2025-11-10 12:00 UTC)).unwrap();
+        assert_eq!(month.since, datetime!(2025-10-01 00:00 +2));
+        assert_eq!(month.until, datetime!(2025-11-01 00:00 +1));
+
+        let month = Interval::resolve(&args, &zone, datetime!(2026-01-05 12:00 UTC)).unwrap();
+        assert_eq!(month.since, datetime!(2025-12-01 00:00 +1));
+    }
+
+    #[test]
+    fn absolute_dates_include_the_last_day() {
+        let interval = resolve("7d", Some("2025-12-01"), Some("2025-12-07")).unwrap();
+        assert_eq!(interval.since, datetime!(2025-12-01 00:00 +1));
+        assert_eq!(interval.until, datetime!(2025-12-08 00:00 +1));
+        assert_eq!(
+            interval.iso8601(),
+            "2025-12-01T00:00:00+01:00/2025-12-08T00:00:00+01:00"
+        );
+        assert_eq!(interval.to_string(), "2025-12-01 00:00 to 2025-12-08 00:00");
+
+        let interval = resolve("7d", Some("2025-12-01T09:15"), None).unwrap();
+        assert_eq!(interval.since, datetime!(2025-12-01 09:15 +1));
+        assert_eq!(interval.until, datetime!(2025-12-10 15:30 +1));
+    }
+
+    #[test]
+    fn relative_range_ends_at_until() {
+        let interval = resolve("2d", None, Some("2025-12-07")).unwrap();
+        assert_eq!(interval.since, datetime!(2025-12-06 00:00 +1));
+        assert_eq!(interval.until, datetime!(2025-12-08 00:00 +1));
+
+        assert!(resolve("today", None, Some("2025-12-07")).is_err());
+    }
+
+    #[test]
+    fn invalid_bounds_are_errors() {
+        assert!(resolve("7d", Some("12/01/2025"), None).is_err());
+        assert!(resolve("7d", Some("2025-12-01T25:00"), None).is_err());
+        assert!(resolve("7d", Some("2025-12-08"), Some("2025-12-01")).is_err());
+    }
+
+    #[test]
+    fn ranges_beyond_the_calendar_are_errors() {
+        let error = resolve("9000000d", None, None).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid range"));
+    }
+}
@@ -2,11 +2,12 @@
 //!
 //! Creates the JSON report that noise consumes.
 
-use crate::signals::SignalCollection;
+use crate::signals::{SignalCollection, Unavailable};
+use crate::timezone::LocalZone;
 use absurd_core::Chaos;
 use absurd_lexicon::moods::Mood;
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::path::Path;
 use time::OffsetDateTime;
 
@@ -22,29 +23,63 @@ pub struct MoodSignature {
     pub generated_at: OffsetDateTime,
     /// Time range analyzed
     pub range: String,
-    /// Source information
-    pub source: SourceInfo,
+    /// Time zone that hours and weekdays were evaluated in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// History sources that fed the analysis (a single `source` in v1)
+    #[serde(alias = "source", deserialize_with = "one_or_many")]
+    pub sources: Vec<SourceInfo>,
     /// Detected mood
     pub mood: MoodInfo,
     /// Detected signals
     pub signals: Vec<SignalInfo>,
+    /// Signal families the history could not support (e.g. no timestamps)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailable: Vec<Unavailable>,
     /// Human-readable notes
     pub notes: Vec<String>,
 }
 
-/// Information about the data source.
+/// Information about one history source.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceInfo {
     /// Shell type (zsh, bash, etc.)
     pub shell: String,
     /// Path to history file
     pub history_path: String,
+    /// History format used for parsing
+    #[serde(default)]
+    pub format: String,
+    /// Why this history file and format were chosen
+    #[serde(default)]
+    pub detection: String,
     /// Whether read was read-only
     pub read_only: bool,
-    /// Number of entries analyzed
+    /// Number of entries parsed from this source
+    #[serde(default)]
+    pub entries_read: usize,
+    /// Number of entries analyzed (in range, duplicates removed)
     pub entries_analyzed: usize,
 }
 
+/// Read a list of sources, or v1's single source object.
+fn one_or_many<'de, D>(deserializer: D) -> std::result::Result<Vec<SourceInfo>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Sources {
+        One(SourceInfo),
+        Many(Vec<SourceInfo>),
+    }
+
+    Ok(match Sources::deserialize(deserializer)? {
+        Sources::One(source) => vec![source],
+        Sources::Many(sources) => sources,
+    })
+}
+
 /// Mood information for the report.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoodInfo {
@@ -56,6 +91,35 @@ pub struct MoodInfo {
     pub confidence: f64,
 }
 
+impl MoodInfo {
+    /// Summarize a detected mood, confidence rounded to 2 decimals.
+    pub fn from_mood(mood: &Mood) -> Self {
+        Self {
+            id: format!("{:?}", mood.id).to_lowercase(),
+            label: mood.label().to_string(),
+            confidence: (mood.confidence * 100.0).round() / 100.0,
+        }
+    }
+}
+
+/// One working session and the mood it was in.
+///
+/// Only times, counts and the mood are reported; session IDs from the
+/// history sources are left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    /// First command in the session (absent for untimed history)
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub start: Option<OffsetDateTime>,
+    /// When the last command finished
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub end: Option<OffsetDateTime>,
+    /// Number of commands in the session
+    pub entries: usize,
+    /// Mood detected from this session alone
+    pub mood: MoodInfo,
+}
+
 /// Signal information for the report.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignalInfo {
@@ -70,7 +134,7 @@ impl MoodSignature {
     pub fn new(
         mood: &Mood,
         signals: &SignalCollection,
-        source: SourceInfo,
+        sources: Vec<SourceInfo>,
         range: &str,
         seed: Option<u64>,
     ) -> Self {
@@ -105,21 +169,49 @@ impl MoodSignature {
         notes.push(format!("status: {}", mood.id.description()));
 
         Self {
-            schema: "absurdtty.mood.v1".to_string(),
+            schema: "absurdtty.mood.v2".to_string(),
             case_id,
             generated_at: now,
             range: range.to_string(),
-            source,
-            mood: MoodInfo {
-                id: format!("{:?}", mood.id).to_lowercase(),
-                label: mood.label().to_string(),
-                confidence: (mood.confidence * 100.0).round() / 100.0,
-            },
+            timezone: None,
+            sources,
+            mood: MoodInfo::from_mood(mood),
             signals: signal_infos,
+            unavailable: signals.unavailable.clone(),
             notes,
         }
     }
 
+    /// Record the zone temporal signals were evaluated in, and
+    /// express the generation time in it.
+    pub fn with_timezone(mut self, zone: &LocalZone) -> Self {
+        self.generated_at = zone.to_local(self.generated_at);
+        self.timezone = Some(zone.name().to_string());
+        self
+    }
+
+    /// Total number of entries analyzed across all sources.
+    pub fn entries_analyzed(&self) -> usize {
+        self.sources.iter().map(|s| s.entries_analyzed).sum()
+    }
+
+    /// One-line description of the sources, e.g. `zsh (812), bash (40)`.
+    fn sources_summary(&self) -> String {
+        self.sources
+            .iter()
+            .map(|s| {
+                // v1 signatures only recorded the shell
+                let format = if s.format.is_empty() {
+                    &s.shell
+                } else {
+                    &s.format
+                };
+                format!("{} ({})", format, s.entries_analyzed)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
     /// Write the signature to a file.
     pub fn write_to_file(&self, path: &Path) -> Result<()> {
         absurd_core::fs_safety::write_json_atomic(path, self)
@@ -151,7 +243,9 @@ impl MoodSignature {
             .row("MOOD", &self.mood.label)
             .row("CONFIDENCE", format!("{:.0}%", self.mood.confidence * 100.0))
             .row("RANGE", &self.range)
-            .row("ENTRIES", self.source.entries_analyzed.to_string())
+            .row("TIMEZONE", self.timezone.as_deref().unwrap_or("unrecorded"))
+            .row("ENTRIES", self.entries_analyzed().to_string())
+            .row("SOURCES", self.sources_summary())
             .build());
 
         output.push('\n');
@@ -174,6 +268,14 @@ impl MoodSignature {
             output.push('\n');
         }
 
+        if !self.unavailable.is_empty() {
+            output.push_str("SIGNALS UNAVAILABLE:\n");
+            for unavailable in &self.unavailable {
+                output.push_str(&format!("  {:24} {}\n", unavailable.family, unavailable.reason));
+            }
+            output.push('\n');
+        }
+
         // Notes
         if !self.notes.is_empty() {
             output.push_str("NOTES:\n");
@@ -206,18 +308,41 @@ mod tests {
         let source = SourceInfo {
             shell: "zsh".to_string(),
             history_path: "/home/user/.zsh_history".to_string(),
+            format: "zsh".to_string(),
+            detection: "content: zsh extended `: ts:dur;` lines".to_string(),
             read_only: true,
+            entries_read: 120,
             entries_analyzed: 100,
         };
 
-        let sig = MoodSignature::new(&mood, &signals, source, "7d", Some(42));
+        let sig = MoodSignature::new(&mood, &signals, vec![source], "7d", Some(42));
         let json = sig.to_json().unwrap();
 
-        assert!(json.contains("absurdtty.mood.v1"));
+        assert!(json.contains("absurdtty.mood.v2"));
         assert!(json.contains("feral"));
         assert!(json.contains("cadence_high"));
     }
 
+    #[test]
+    fn entries_summed_across_sources() {
+        let source = |format: &str, analyzed| SourceInfo {
+            shell: format.to_string(),
+            history_path: format!("/home/user/.{}_history", format),
+            format: format.to_string(),
+            detection: "--history given".to_string(),
+            read_only: true,
+            entries_read: analyzed,
+            entries_analyzed: analyzed,
+        };
+
+        let mood = Mood::new(MoodId::Methodical, 0.6);
+        let sources = vec![source("zsh", 80), source("bash", 20)];
+        let sig = MoodSignature::new(&mood, &SignalCollection::new(), sources, "7d", None);
+
+        assert_eq!(sig.entries_analyzed(), 100);
+        assert_eq!(sig.sources_summary(), "zsh (80), bash (20)");
+    }
+
     #[test]
     fn summary_renders() {
         let mood = Mood::new(MoodId::Exhausted, 0.7);
@@ -226,15 +351,75 @@ mod tests {
         let source = SourceInfo {
             shell: "zsh".to_string(),
             history_path: "/test".to_string(),
+            format: "zsh".to_string(),
+            detection: "--history given".to_string(),
             read_only: true,
+            entries_read: 50,
             entries_analyzed: 50,
         };
 
-        let sig = MoodSignature::new(&mood, &signals, source, "7d", None);
+        let sig = MoodSignature::new(&mood, &signals, vec![source], "7d", None);
         let summary = sig.to_summary();
 
         assert!(summary.contains("MOOD SIGNATURE REPORT"));
         assert!(summary.contains("exhausted"));
+        assert!(summary.contains("zsh (50)"));
+    }
+
+    #[test]
+    fn unavailable_families_reported() {
+        let mood = Mood::new(MoodId::AmbientDrift, 0.4);
+        let mut signals = SignalCollection::new();
+        signals.mark_unavailable("temporal", "history has no timestamps");
+
+        let sig = MoodSignature::new(&mood, &signals, Vec::new(), "7d", None);
+        assert!(sig.to_json().unwrap().contains("\"unavailable\""));
+        assert!(sig.to_summary().contains("SIGNALS UNAVAILABLE"));
+
+        // Older signatures without the field still parse
+        let sig = MoodSignature::new(&mood, &SignalCollection::new(), Vec::new(), "7d", None);
+        let json = sig.to_json().unwrap();
+        assert!(!json.contains("unavailable"));
+        let parsed: MoodSignature = serde_json::from_str(&json).unwrap();
+        assert!(parsed.unavailable.is_empty());
+    }
+
+    #[test]
+    fn timezone_recorded() {
+        let mood = Mood::new(MoodId::Methodical, 0.6);
+        let zone = LocalZone::named("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        let sig = MoodSignature::new(&mood, &SignalCollection::new(), Vec::new(), "7d", None)
+            .with_timezone(&zone);
+
+        assert_eq!(sig.timezone.as_deref(), Some("CET-1CEST,M3.5.0,M10.5.0/3"));
+        assert!(sig.to_json().unwrap().contains("\"timezone\""));
+        assert!(sig.to_summary().contains("TIMEZONE"));
     }
-}
 
+    #[test]
+    fn v1_signature_parses() {
+        let json = r#"{
+            "schema": "absurdtty.mood.v1",
+            "case_id": "AB-20251212-001",
+            "generated_at": "2025-12-12T11:32:00+01:00",
+            "range": "7d",
+            "source": {
+                "shell": "zsh",
+                "history_path": "/home/user/.zsh_history",
+                "read_only": true,
+                "entries_analyzed": 812
+            },
+            "mood": { "id": "methodical", "label": "methodical", "confidence": 0.74 },
+            "signals": [{ "id": "cadence_high", "score": 0.81 }],
+            "notes": ["status: non-binding"]
+        }"#;
+
+        let sig: MoodSignature = serde_json::from_str(json).unwrap();
+        assert_eq!(sig.sources.len(), 1);
+        assert_eq!(sig.sources[0].shell, "zsh");
+        assert_eq!(sig.sources[0].entries_read, 0);
+        assert_eq!(sig.entries_analyzed(), 812);
+        assert!(sig.timezone.is_none());
+        assert!(sig.to_summary().contains("zsh (812)"));
+    }
+}
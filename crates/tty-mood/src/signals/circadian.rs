@@ -0,0 +1,264 @@
+//! Circadian signal detection.
+//!
+//! Measures rest, as far as a shell can tell: how long the terminal
+//! stayed quiet each night, how long work went on without a break, and
+//! whether the day starts and ends at roughly the same time.
+//!
+//! Days run from 04:00 to 04:00, so a session that goes on past
+//! midnight still belongs to the evening it started in. Only
+//! timestamps are needed; a running command counts as activity.
+
+use super::{Signal, SignalCollection};
+use crate::history::HistoryEntry;
+use std::collections::BTreeMap;
+use time::{Date, Duration, OffsetDateTime};
+
+/// Where one day ends and the next begins.
+const DAY_START: Duration = Duration::hours(4);
+
+/// Minimum active days before rest says anything.
+const MIN_DAYS: usize = 3;
+
+/// Minimum active days before schedule regularity says anything.
+const MIN_SCHEDULE_DAYS: usize = 5;
+
+/// Nights quieter than this are too short.
+const SHORT_REST: Duration = Duration::hours(6);
+
+/// A pause of at least this long ends a streak.
+const STREAK_BREAK: Duration = Duration::minutes(15);
+
+/// Streaks from this long on are marathons.
+const MARATHON: Duration = Duration::hours(4);
+
+/// Circadian signal analyzer.
+pub struct CircadianSignals;
+
+/// Commands of one day (04:00 to 04:00).
+#[derive(Debug)]
+struct Day {
+    /// Minutes after 04:00 of the first and last command
+    first: i64,
+    last: i64,
+    /// Longest pause ending this day, if activity came before it
+    rest: Option<Duration>,
+}
+
+impl CircadianSignals {
+    /// Analyze rest, streaks and schedule in history entries.
+    pub fn analyze(entries: &[HistoryEntry]) -> SignalCollection {
+        let mut signals = SignalCollection::new();
+
+        // (start, end) of every command; a duration too large to add
+        // comes from a corrupt history line and counts as unknown
+        let mut activity: Vec<(OffsetDateTime, OffsetDateTime)> = entries
+            .iter()
+            .filter_map(|e| {
+                e.timestamp.map(|ts| {
+                    let end = ts.checked_add(e.duration.unwrap_or_default());
+                    (ts, end.unwrap_or(ts))
+                })
+            })
+            .collect();
+
+        if activity.is_empty() {
+            if !entries.is_empty() {
+                signals.mark_unavailable("circadian", "history has no timestamps");
+            }
+            return signals;
+        }
+
+        activity.sort();
+        let days = Self::days(&activity);
+
+        if days.len() < MIN_DAYS {
+            return signals;
+        }
+
+        // Short nights
+        let rests: Vec<Duration> = days.values().filter_map(|d| d.rest).collect();
+        let short: Vec<Duration> = rests.iter().copied().filter(|r| *r < SHORT_REST).collect();
+        let short_share = short.len() as f64 / rests.len().max(1) as f64;
+        if short.len() >= 2 && short_share >= 0.25 {
+            let shortest = short.iter().min().copied().unwrap_or_default();
+            signals.add(
+                Signal::new("sleep_debt", (short_share * 1.5).min(1.0)).with_note(format!(
+                    "{} of {} nights under 6h without commands (shortest {})",
+                    short.len(),
+                    rests.len(),
+                    format_hours(shortest)
+                )),
+            );
+        }
+
+        // Long stretches without a break
+        let streak = Self::longest_streak(&activity);
+        if streak >= MARATHON {
+            let hours = streak.as_seconds_f64() / 3600.0;
+            signals.add(
+                Signal::new("marathon_streak", ((hours - 3.0) / 5.0).min(1.0)).with_note(format!(
+                    "Longest streak {} without a 15-minute break",
+                    format_hours(streak)
+                )),
+            );
+        }
+
+        // Days starting and ending at different times
+        if days.len() >= MIN_SCHEDULE_DAYS {
+            let first: Vec<i64> = days.values().map(|d| d.first).collect();
+            let last: Vec<i64> = days.values().map(|d| d.last).collect();
+            let (first_spread, last_spread) = (spread_hours(&first), spread_hours(&last));
+            let spread = (first_spread + last_spread) / 2.0;
+            if spread > 1.5 {
+                signals.add(
+                    Signal::new("irregular_schedule", ((spread - 1.0) / 3.0).min(1.0)).with_note(
+                        format!(
+                            "First command varies by ±{:.1}h, last by ±{:.1}h",
+                            first_spread, last_spread
+                        ),
+                    ),
+                );
+            }
+        }
+
+        signals
+    }
+
+    /// Group activity into days, with the longest pause ending in each.
+    fn days(activity: &[(OffsetDateTime, OffsetDateTime)]) -> BTreeMap<Date, Day> {
+        let mut days: BTreeMap<Date, Day> = BTreeMap::new();
+        let mut last_active: Option<OffsetDateTime> = None;
+
+        for (start, end) in activity {
+            let shifted = *start - DAY_START;
+            let minutes = i64::from(shifted.hour()) * 60 + i64::from(shifted.minute());
+            let pause = last_active.map(|last| *start - last);
+
+            // The first day's rest began before history did
+            let day = days.entry(shifted.date()).or_insert(Day {
+                first: minutes,
+                last: minutes,
+                rest: pause,
+            });
+            day.last = minutes;
+            if let (Some(rest), Some(pause)) = (day.rest, pause) {
+                day.rest = Some(rest.max(pause));
+            }
+
+            last_active = Some(last_active.map_or(*end, |last| last.max(*end)));
+        }
+
+        days
+    }
+
+    /// Longest run of activity without a pause of [`STREAK_BREAK`].
+    fn longest_streak(activity: &[(OffsetDateTime, OffsetDateTime)]) -> Duration {
+        let mut longest = Duration::ZERO;
+        let mut streak: Option<(OffsetDateTime, OffsetDateTime)> = None;
+
+        for (start, end) in activity {
+            streak = match streak {
+                Some((began, active)) if *start - active < STREAK_BREAK => {
+                    Some((began, active.max(*end)))
+                }
+                _ => Some((*start, *end)),
+            };
+            if let Some((began, active)) = streak {
+                longest = longest.max(active - began);
+            }
+        }
+
+        longest
+    }
+}
+
+/// Standard deviation of minute values, in hours.
+fn spread_hours(minutes: &[i64]) -> f64 {
+    let n = minutes.len() as f64;
+    let mean = minutes.iter().sum::<i64>() as f64 / n;
+    let variance = minutes
+        .iter()
+        .map(|m| (*m as f64 - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    variance.sqrt() / 60.0
+}
+
+/// Format a duration as `5h40m`.
+fn format_hours(duration: Duration) -> String {
+    let minutes = duration.whole_minutes();
+    format!("{}h{:02}m", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    /// Entries every 10 minutes from `start`, for `hours`, on consecutive days.
+    fn days(start: OffsetDateTime, hours: &[(i64, i64)]) -> Vec<HistoryEntry> {
+        let mut entries = Vec::new();
+        for (day, (offset, length)) in hours.iter().enumerate() {
+            let begin = start + Duration::days(day as i64) + Duration::hours(*offset);
+            for step in 0..=length * 6 {
+                let ts = begin + Duration::minutes(step * 10);
+                entries.push(HistoryEntry::new("ls".into(), Some(ts), entries.len() + 1));
+            }
+        }
+        entries
+    }
+
+    #[test]
+    fn short_nights_are_sleep_debt() {
+        // 08:00 to 03:00 the next night, every day: five hours of quiet
+        let entries = days(datetime!(2025-12-08 08:00 UTC), &[(0, 19); 5]);
+
+        let signals = CircadianSignals::analyze(&entries);
+        let debt = signals.get("sleep_debt").unwrap();
+        assert_eq!(debt.score, 1.0);
+        assert_eq!(
+            debt.note.as_deref(),
+            Some("4 of 4 nights under 6h without commands (shortest 5h00m)")
+        );
+        assert!(signals.score("marathon_streak") > 0.9);
+        assert!(signals.get("irregular_schedule").is_none());
+    }
+
+    #[test]
+    fn office_hours_are_rested_and_regular() {
+        // 09:00 to 12:00 and 13:00 to 16:00
+        let mut entries = days(datetime!(2025-12-08 09:00 UTC), &[(0, 3); 5]);
+        entries.extend(days(datetime!(2025-12-08 13:00 UTC), &[(0, 3); 5]));
+        entries.sort_by_key(|e| e.timestamp);
+
+        let signals = CircadianSignals::analyze(&entries);
+        assert!(signals.signals.is_empty());
+    }
+
+    #[test]
+    fn oversized_durations_count_as_unknown() {
+        // A corrupt zsh `: ts:duration;` field
+        let mut entries = days(datetime!(2025-12-08 09:00 UTC), &[(0, 3); 5]);
+        entries[0].duration = Some(Duration::seconds(9_000_000_000_000_000));
+
+        let signals = CircadianSignals::analyze(&entries);
+        assert!(signals.signals.is_empty());
+    }
+
+    #[test]
+    fn shifting_days_are_irregular() {
+        let entries = days(
+            datetime!(2025-12-08 06:00 UTC),
+            &[(0, 2), (5, 2), (1, 6), (10, 3), (3, 1), (13, 2)],
+        );
+
+        let signals = CircadianSignals::analyze(&entries);
+        let irregular = signals.get("irregular_schedule").unwrap();
+        assert!(irregular.score > 0.5);
+        assert!(irregular
+            .note
+            .as_deref()
+            .unwrap()
+            .starts_with("First command varies by ±"));
+    }
+}
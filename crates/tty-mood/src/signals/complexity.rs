@@ -0,0 +1,139 @@
+//! Command-line complexity signal detection.
+//!
+//! Analyzes how elaborate command lines are: 300-character one-liners
+//! with loops, pipes and nested substitutions, or nothing but bare
+//! `ls` and `git status`.
+//!
+//! Works from each entry's [`LineShape`](crate::history::lexer::LineShape),
+//! which counts operators but keeps no text. Shapes are never cached,
+//! so when some entries come from the history cursor the family is
+//! unavailable rather than measured on the newest lines alone. Only
+//! the resulting scores end up in the signature.
+
+use super::{Signal, SignalCollection};
+use crate::history::lexer::LineShape;
+use crate::history::HistoryEntry;
+
+/// Minimum measured lines before complexity says anything.
+const MIN_LINES: usize = 10;
+
+/// Lines at least this long are baroque whatever they contain.
+const BAROQUE_LENGTH: usize = 200;
+
+/// Lines at most this long, without operators, are minimal.
+const MINIMAL_LENGTH: usize = 20;
+
+/// Complexity signal analyzer.
+pub struct ComplexitySignals;
+
+impl ComplexitySignals {
+    /// Analyze command-line complexity in history entries.
+    pub fn analyze(entries: &[HistoryEntry]) -> SignalCollection {
+        let mut signals = SignalCollection::new();
+
+        let shapes: Vec<&LineShape> = entries.iter().filter_map(|e| e.shape.as_ref()).collect();
+
+        // Every parser measures lines; entries without a shape come
+        // from the history cursor
+        if shapes.len() < entries.len() {
+            signals.mark_unavailable(
+                "complexity",
+                "cached entries have no line shapes (run with --full)",
+            );
+            return signals;
+        }
+
+        if shapes.len() < MIN_LINES {
+            return signals;
+        }
+
+        let total = shapes.len() as f64;
+
+        // Long pipelines, loops and nested substitutions
+        let baroque = shapes.iter().filter(|s| Self::is_baroque(s)).count();
+        let baroque_share = baroque as f64 / total;
+        if baroque_share > 0.05 {
+            let longest = shapes.iter().map(|s| s.length).max().unwrap_or(0);
+            signals.add(
+                Signal::new("pipeline_baroque", (baroque_share * 4.0).min(1.0)).with_note(format!(
+                    "{}% elaborate one-liners (longest {} characters)",
+                    (baroque_share * 100.0) as u32,
+                    longest
+                )),
+            );
+        }
+
+        // Short commands without a single operator
+        let minimal = shapes
+            .iter()
+            .filter(|s| s.is_plain() && s.length <= MINIMAL_LENGTH)
+            .count();
+        let minimal_share = minimal as f64 / total;
+        if minimal_share > 0.85 && baroque == 0 {
+            signals.add(
+                Signal::new("minimalist_invocation", (minimal_share - 0.75) * 4.0).with_note(
+                    format!(
+                        "{}% short commands without pipes or redirections",
+                        (minimal_share * 100.0) as u32
+                    ),
+                ),
+            );
+        }
+
+        signals
+    }
+
+    /// Whether a line is elaborate enough to count as baroque.
+    ///
+    /// Substitutions and loops weigh double: they nest whole commands.
+    fn is_baroque(shape: &LineShape) -> bool {
+        let operators = shape.pipes + shape.redirections + 2 * (shape.substitutions + shape.loops);
+        shape.length >= BAROQUE_LENGTH || operators >= 4
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(lines: &[&str]) -> Vec<HistoryEntry> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| HistoryEntry::from_command_line(line, None, i + 1))
+            .collect()
+    }
+
+    #[test]
+    fn cached_entries_make_complexity_unavailable() {
+        let mut entries = entries(&["git status"; 12]);
+        entries[0].shape = None;
+
+        let signals = ComplexitySignals::analyze(&entries);
+        assert!(signals.signals.is_empty());
+        assert!(!signals.is_available("complexity"));
+    }
+
+    #[test]
+    fn one_liners_are_baroque() {
+        let mut lines = vec![
+            "for f in $(ls *.log); do grep -c ERROR \"$f\" | sort -n >> counts; done",
+            "kubectl get pods -o json | jq -r '.items[].metadata.name' | xargs -n1 kubectl logs | grep -i panic 2>/dev/null",
+        ];
+        lines.extend(["git status"; 10]);
+
+        let signals = ComplexitySignals::analyze(&entries(&lines));
+        assert!(signals.score("pipeline_baroque") > 0.6);
+        assert!(signals.get("minimalist_invocation").is_none());
+    }
+
+    #[test]
+    fn bare_commands_are_minimalist() {
+        let mut lines = vec!["cargo build --release"];
+        lines.extend(["ls", "git status", "cd src", "make"].repeat(5));
+
+        let signals = ComplexitySignals::analyze(&entries(&lines));
+        assert!(signals.score("minimalist_invocation") > 0.8);
+        assert!(signals.get("pipeline_baroque").is_none());
+    }
+}
@@ -1,6 +1,9 @@
 //! Command diversity signal detection.
 //!
 //! Analyzes the variety of commands and tools being used.
+//!
+//! Counts every command a line invokes, so `cat x | grep y && cargo test`
+//! contributes `cat`, `grep` and `cargo`.
 
 use super::{Signal, SignalCollection};
 use crate::history::HistoryEntry;
@@ -18,9 +21,14 @@ impl DiversitySignals {
             return signals;
         }
 
+        let invoked: Vec<&str> = invoked_commands(entries).collect();
+        if invoked.is_empty() {
+            return signals;
+        }
+
         // Count unique commands
-        let unique_commands: HashSet<_> = entries.iter().map(|e| e.command_name()).collect();
-        let diversity_ratio = unique_commands.len() as f64 / entries.len() as f64;
+        let unique_commands: HashSet<_> = invoked.iter().collect();
+        let diversity_ratio = unique_commands.len() as f64 / invoked.len() as f64;
 
         // High diversity: many different commands
         if diversity_ratio > 0.5 {
@@ -28,7 +36,7 @@ impl DiversitySignals {
                 Signal::new("command_diversity_high", diversity_ratio)
                     .with_note(format!("{} unique commands", unique_commands.len())),
             );
-        } else if diversity_ratio < 0.2 && entries.len() > 20 {
+        } else if diversity_ratio < 0.2 && invoked.len() > 20 {
             signals.add(
                 Signal::new("command_diversity_low", 1.0 - diversity_ratio)
                     .with_note("Limited command variety"),
@@ -36,7 +44,7 @@ impl DiversitySignals {
         }
 
         // Tool fixation (one tool dominates)
-        let fixation = Self::detect_tool_fixation(entries);
+        let fixation = Self::detect_tool_fixation(&invoked);
         if let Some((tool, score)) = fixation {
             signals.add(
                 Signal::new("tool_fixation", score)
@@ -44,14 +52,19 @@ impl DiversitySignals {
             );
         }
 
-        // Context switching (different project directories)
-        let context_score = Self::detect_context_switching(entries);
+        // Context switching (frequent `cd`; see `projects` for real project switches)
+        let context_score = Self::detect_context_switching(&invoked);
         if context_score > 0.3 {
             signals.add(Signal::new("context_switching", context_score));
         }
 
+        // Commands typed through aliases and functions
+        if let Some(signal) = Self::detect_alias_dependence(entries) {
+            signals.add(signal);
+        }
+
         // Tool categories
-        let category_signals = Self::analyze_tool_categories(entries);
+        let category_signals = Self::analyze_tool_categories(&invoked);
         for signal in category_signals {
             signals.add(signal);
         }
@@ -60,16 +73,16 @@ impl DiversitySignals {
     }
 
     /// Detect if one tool dominates the session.
-    fn detect_tool_fixation(entries: &[HistoryEntry]) -> Option<(String, f64)> {
+    fn detect_tool_fixation(invoked: &[&str]) -> Option<(String, f64)> {
         let mut counts: HashMap<&str, usize> = HashMap::new();
 
-        for entry in entries {
-            *counts.entry(entry.command_name()).or_insert(0) += 1;
+        for command in invoked {
+            *counts.entry(command).or_insert(0) += 1;
         }
 
         let (top_cmd, top_count) = counts.iter().max_by_key(|(_, c)| *c)?;
 
-        let ratio = *top_count as f64 / entries.len() as f64;
+        let ratio = *top_count as f64 / invoked.len() as f64;
 
         // Fixation if > 40% of commands are the same tool
         if ratio > 0.4 {
@@ -80,24 +93,41 @@ impl DiversitySignals {
     }
 
     /// Detect context switching (cd commands to different paths).
-    fn detect_context_switching(entries: &[HistoryEntry]) -> f64 {
-        let cd_count = entries
-            .iter()
-            .filter(|e| e.command_name() == "cd")
-            .count();
+    fn detect_context_switching(invoked: &[&str]) -> f64 {
+        let cd_count = invoked.iter().filter(|c| **c == "cd").count();
 
-        if entries.len() < 10 {
+        if invoked.len() < 10 {
             return 0.0;
         }
 
         // High cd ratio suggests context switching
-        let cd_ratio = cd_count as f64 / entries.len() as f64;
+        let cd_ratio = cd_count as f64 / invoked.len() as f64;
 
         (cd_ratio * 5.0).min(1.0)
     }
 
+    /// Detect reliance on aliases and functions.
+    fn detect_alias_dependence(entries: &[HistoryEntry]) -> Option<Signal> {
+        let aliased: Vec<&str> = entries.iter().filter_map(|e| e.alias.as_deref()).collect();
+        let ratio = aliased.len() as f64 / entries.len() as f64;
+
+        // Dependence if > 20% of commands go through an alias
+        if ratio <= 0.2 {
+            return None;
+        }
+
+        let distinct: HashSet<_> = aliased.iter().collect();
+        Some(
+            Signal::new("alias_dependence", (ratio * 2.0).min(1.0)).with_note(format!(
+                "{:.0}% of commands via {} aliases",
+                ratio * 100.0,
+                distinct.len()
+            )),
+        )
+    }
+
     /// Analyze which categories of tools are being used.
-    fn analyze_tool_categories(entries: &[HistoryEntry]) -> Vec<Signal> {
+    fn analyze_tool_categories(invoked: &[&str]) -> Vec<Signal> {
         let mut signals = Vec::new();
 
         let git_commands = ["git", "gh", "hub", "tig", "lazygit"];
@@ -106,13 +136,10 @@ impl DiversitySignals {
         let system_commands = ["systemctl", "journalctl", "dmesg", "htop", "top", "ps", "kill"];
         let package_commands = ["pacman", "apt", "yay", "brew", "dnf", "pip", "cargo"];
 
-        let total = entries.len() as f64;
+        let total = invoked.len() as f64;
 
         // Git usage
-        let git_count = entries
-            .iter()
-            .filter(|e| git_commands.contains(&e.command_name()))
-            .count();
+        let git_count = invoked.iter().filter(|c| git_commands.contains(c)).count();
         if git_count as f64 / total > 0.15 {
             signals.push(
                 Signal::new("git_heavy", (git_count as f64 / total * 3.0).min(1.0))
@@ -121,37 +148,25 @@ impl DiversitySignals {
         }
 
         // Editor usage
-        let editor_count = entries
-            .iter()
-            .filter(|e| editor_commands.contains(&e.command_name()))
-            .count();
+        let editor_count = invoked.iter().filter(|c| editor_commands.contains(c)).count();
         if editor_count as f64 / total > 0.1 {
             signals.push(Signal::new("editor_focused", (editor_count as f64 / total * 4.0).min(1.0)));
         }
 
         // Build tools
-        let build_count = entries
-            .iter()
-            .filter(|e| build_commands.contains(&e.command_name()))
-            .count();
+        let build_count = invoked.iter().filter(|c| build_commands.contains(c)).count();
         if build_count as f64 / total > 0.1 {
             signals.push(Signal::new("build_cycle", (build_count as f64 / total * 4.0).min(1.0)));
         }
 
         // System administration
-        let system_count = entries
-            .iter()
-            .filter(|e| system_commands.contains(&e.command_name()))
-            .count();
+        let system_count = invoked.iter().filter(|c| system_commands.contains(c)).count();
         if system_count as f64 / total > 0.1 {
             signals.push(Signal::new("system_admin", (system_count as f64 / total * 4.0).min(1.0)));
         }
 
         // Package management
-        let package_count = entries
-            .iter()
-            .filter(|e| package_commands.contains(&e.command_name()))
-            .count();
+        let package_count = invoked.iter().filter(|c| package_commands.contains(c)).count();
         if package_count as f64 / total > 0.1 {
             signals.push(Signal::new("package_operations", (package_count as f64 / total * 4.0).min(1.0)));
         }
@@ -160,6 +175,13 @@ impl DiversitySignals {
     }
 }
 
+/// Every command invoked across all entries.
+fn invoked_commands(entries: &[HistoryEntry]) -> impl Iterator<Item = &str> {
+    entries
+        .iter()
+        .flat_map(|e| e.commands.iter().map(String::as_str))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -199,5 +221,36 @@ mod tests {
         let signals = DiversitySignals::analyze(&entries);
         assert!(signals.score("git_heavy") > 0.5);
     }
-}
 
+    #[test]
+    fn pipelines_count_every_command() {
+        let entries: Vec<_> = (0..10)
+            .map(|i| HistoryEntry::from_command_line("cat x | grep y && cargo test", None, i + 1))
+            .collect();
+
+        let signals = DiversitySignals::analyze(&entries);
+        // cargo is a third of the invocations, not absent
+        assert!(signals.score("build_cycle") > 0.9);
+        assert!(signals.get("tool_fixation").is_none());
+    }
+
+    #[test]
+    fn alias_dependence_detected() {
+        let entries: Vec<_> = ["gst", "gco", "ls", "k", "gst"]
+            .iter()
+            .enumerate()
+            .map(|(i, cmd)| {
+                let mut entry = HistoryEntry::new(cmd.to_string(), None, i + 1);
+                if *cmd != "ls" {
+                    entry.alias = Some(cmd.to_string());
+                }
+                entry
+            })
+            .collect();
+
+        let signals = DiversitySignals::analyze(&entries);
+        assert!(signals.score("alias_dependence") > 0.9);
+        let note = signals.get("alias_dependence").unwrap().note.clone().unwrap();
+        assert!(note.contains("3 aliases"));
+    }
+}
@@ -0,0 +1,197 @@
+//! Command duration signal detection.
+//!
+//! Analyzes how long commands ran: waiting out long builds, cutting
+//! commands short, or never running anything that takes a while.
+//!
+//! Durations come from zsh's extended history (whole seconds), Atuin
+//! and nushell. zsh writes a duration of 0 for every command when
+//! `INC_APPEND_HISTORY` is set, so all-zero durations count as none.
+
+use super::{Signal, SignalCollection};
+use crate::history::HistoryEntry;
+use std::collections::HashMap;
+use time::Duration;
+
+/// Minimum timed commands before durations say anything.
+const MIN_TIMED: usize = 10;
+
+/// Commands running at least this long are waited on.
+const LONG_RUNNING: Duration = Duration::minutes(1);
+
+/// Commands ending this quickly may have been cut short.
+const CUT_SHORT: Duration = Duration::seconds(2);
+
+/// Exit statuses of commands killed by Ctrl-C (SIGINT) or SIGTERM.
+const INTERRUPTED: [i32; 2] = [130, 143];
+
+/// Duration signal analyzer.
+pub struct DurationSignals;
+
+impl DurationSignals {
+    /// Analyze command durations in history entries.
+    pub fn analyze(entries: &[HistoryEntry]) -> SignalCollection {
+        let mut signals = SignalCollection::new();
+
+        let timed: Vec<(&HistoryEntry, Duration)> = entries
+            .iter()
+            .filter_map(|e| e.duration.map(|d| (e, d)))
+            .collect();
+
+        if timed.iter().all(|(_, d)| d.is_zero()) {
+            signals.mark_unavailable("duration", "history has no command durations");
+            return signals;
+        }
+
+        if timed.len() < MIN_TIMED {
+            return signals;
+        }
+
+        let total = timed.len() as f64;
+
+        // Long-running commands: builds, test suites, downloads
+        let long: Vec<Duration> = timed
+            .iter()
+            .map(|(_, d)| *d)
+            .filter(|d| *d >= LONG_RUNNING)
+            .collect();
+        let long_share = long.len() as f64 / total;
+        if long_share > 0.05 {
+            let longest = long.iter().max().copied().unwrap_or_default();
+            signals.add(
+                Signal::new("long_running_vigil", (long_share * 5.0).min(1.0)).with_note(format!(
+                    "{} commands ran over a minute (longest {})",
+                    long.len(),
+                    format_minutes(longest)
+                )),
+            );
+        }
+
+        // Commands killed within a second or two
+        let cut_short = Self::count_cut_short(&timed);
+        let cut_share = cut_short as f64 / total;
+        if cut_share > 0.05 {
+            signals.add(
+                Signal::new("impatience", (cut_share * 5.0).min(1.0))
+                    .with_note(format!("{} commands cut short", cut_short)),
+            );
+        }
+
+        // Nothing that takes a while, ever
+        let instant = timed.iter().filter(|(_, d)| *d < Duration::SECOND).count();
+        let instant_share = instant as f64 / total;
+        if instant_share > 0.9 && long.is_empty() {
+            signals.add(
+                Signal::new("instant_gratification", (instant_share - 0.8) * 5.0).with_note(
+                    format!(
+                        "{}% of commands finished within a second",
+                        (instant_share * 100.0) as u32
+                    ),
+                ),
+            );
+        }
+
+        signals
+    }
+
+    /// Count commands killed shortly after they started.
+    ///
+    /// A command counts when its exit status says it was interrupted,
+    /// or, for sources without exit status, when it ended within two
+    /// seconds although the same command ran ten times as long elsewhere.
+    fn count_cut_short(timed: &[(&HistoryEntry, Duration)]) -> usize {
+        let mut longest: HashMap<&str, Duration> = HashMap::new();
+        for (entry, duration) in timed {
+            let slot = longest.entry(entry.command_name()).or_default();
+            *slot = (*slot).max(*duration);
+        }
+
+        timed
+            .iter()
+            .filter(|(_, duration)| *duration <= CUT_SHORT)
+            .filter(|(entry, duration)| match entry.exit_status {
+                Some(code) => INTERRUPTED.contains(&code),
+                None => longest[entry.command_name()] >= (*duration).max(Duration::SECOND) * 10,
+            })
+            .count()
+    }
+}
+
+/// Format a duration as `42m` or `1h05m`.
+fn format_minutes(duration: Duration) -> String {
+    let minutes = duration.whole_minutes();
+    if minutes >= 60 {
+        format!("{}h{:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timed(pairs: &[(&str, i64)]) -> Vec<HistoryEntry> {
+        pairs
+            .iter()
+            .enumerate()
+            .map(|(i, (cmd, secs))| {
+                let mut entry = HistoryEntry::new(cmd.to_string(), None, i + 1);
+                entry.duration = Some(Duration::seconds(*secs));
+                entry
+            })
+            .collect()
+    }
+
+    #[test]
+    fn long_builds_are_a_vigil() {
+        let mut pairs = vec![("cargo", 300), ("cargo", 4000)];
+        pairs.extend([("ls", 0); 10]);
+        let signals = DurationSignals::analyze(&timed(&pairs));
+
+        let vigil = signals.get("long_running_vigil").unwrap();
+        assert!(vigil.score > 0.8);
+        assert_eq!(
+            vigil.note.as_deref(),
+            Some("2 commands ran over a minute (longest 1h06m)")
+        );
+        assert!(signals.get("instant_gratification").is_none());
+    }
+
+    #[test]
+    fn interrupted_commands_are_impatience() {
+        let mut entries = timed(&[("make", 1); 4]);
+        for entry in &mut entries {
+            entry.exit_status = Some(130);
+        }
+        entries.extend(timed(&[("git", 0); 8]));
+
+        let signals = DurationSignals::analyze(&entries);
+        assert_eq!(signals.score("impatience"), 1.0);
+    }
+
+    #[test]
+    fn short_runs_of_slow_commands_are_impatience() {
+        let mut pairs = vec![("cargo", 120), ("cargo", 1), ("cargo", 0), ("cargo", 2)];
+        pairs.extend([("ls", 0); 8]);
+        let signals = DurationSignals::analyze(&timed(&pairs));
+
+        assert!(signals.score("impatience") > 0.9);
+        assert_eq!(
+            signals.get("impatience").unwrap().note.as_deref(),
+            Some("3 commands cut short")
+        );
+    }
+
+    #[test]
+    fn sub_second_only_is_instant_gratification() {
+        let signals = DurationSignals::analyze(&timed(&[("ls", 0); 20]));
+        assert!(signals.get("instant_gratification").is_none());
+        assert!(!signals.is_available("duration"));
+
+        let mut pairs = vec![("grep", 1)];
+        pairs.extend([("ls", 0); 19]);
+        let signals = DurationSignals::analyze(&timed(&pairs));
+        assert!(signals.score("instant_gratification") > 0.6);
+        assert!(signals.get("impatience").is_none());
+    }
+}
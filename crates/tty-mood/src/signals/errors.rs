@@ -6,6 +6,9 @@ use super::{Signal, SignalCollection};
 use crate::history::HistoryEntry;
 use std::collections::HashMap;
 
+/// Exit status shells use for a command that doesn't exist.
+const COMMAND_NOT_FOUND: i32 = 127;
+
 /// Error signal analyzer.
 pub struct ErrorSignals;
 
@@ -19,7 +22,7 @@ impl ErrorSignals {
         }
 
         // Typo rate analysis
-        let typo_count = entries.iter().filter(|e| e.looks_like_typo()).count();
+        let typo_count = entries.iter().filter(|e| Self::is_typo(e)).count();
         let typo_rate = typo_count as f64 / entries.len() as f64;
 
         if typo_rate > 0.1 {
@@ -33,6 +36,18 @@ impl ErrorSignals {
             signals.add(Signal::new("typo_rate_low", 1.0 - typo_rate * 10.0));
         }
 
+        // Real failure rate (only for sources that record exit status)
+        if let Some(failure_rate) = Self::failure_rate(entries) {
+            if failure_rate > 0.15 {
+                signals.add(
+                    Signal::new("failure_rate_high", (failure_rate * 3.0).min(1.0))
+                        .with_note(format!("{}% of commands failed", (failure_rate * 100.0) as u32)),
+                );
+            } else if failure_rate < 0.05 && entries.len() > 50 {
+                signals.add(Signal::new("failure_rate_low", 1.0 - failure_rate * 20.0));
+            }
+        }
+
         // Repeated commands analysis
         let repeat_score = Self::detect_repeats(entries);
         if repeat_score > 0.2 {
@@ -60,6 +75,27 @@ impl ErrorSignals {
         signals
     }
 
+    /// Whether an entry is a possible typo.
+    ///
+    /// "command not found" is a typo we know about.
+    pub fn is_typo(entry: &HistoryEntry) -> bool {
+        entry.looks_like_typo() || entry.exit_status == Some(COMMAND_NOT_FOUND)
+    }
+
+    /// Share of failed commands among entries with a known exit status.
+    ///
+    /// Returns None when the source doesn't record exit status.
+    fn failure_rate(entries: &[HistoryEntry]) -> Option<f64> {
+        let with_status: Vec<_> = entries.iter().filter(|e| e.exit_status.is_some()).collect();
+
+        if with_status.len() < 5 {
+            return None;
+        }
+
+        let failed = with_status.iter().filter(|e| e.failed()).count();
+        Some(failed as f64 / with_status.len() as f64)
+    }
+
     /// Detect repeated consecutive commands.
     fn detect_repeats(entries: &[HistoryEntry]) -> f64 {
         if entries.len() < 2 {
@@ -89,8 +125,11 @@ impl ErrorSignals {
             let cmd1 = &window[0].command;
             let cmd2 = &window[1].command;
 
+            // A typo followed by the command it was meant to be
+            let retyped = window[0].intended_command() == Some(window[1].command_name());
+
             // Check if commands are similar (likely correction)
-            if Self::is_likely_correction(cmd1, cmd2) {
+            if retyped || Self::is_likely_correction(cmd1, cmd2) {
                 correction_count += 1;
             }
         }
@@ -162,6 +201,7 @@ impl ErrorSignals {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::history::dictionary::CommandDictionary;
 
     #[test]
     fn typo_detection() {
@@ -188,6 +228,62 @@ mod tests {
         assert!(signals.score("repeat_commands") > 0.5);
     }
 
+    #[test]
+    fn failure_rate_from_exit_status() {
+        let entries: Vec<_> = [0, 1, 0, 2, 127, 0]
+            .iter()
+            .enumerate()
+            .map(|(i, code)| {
+                let mut entry = HistoryEntry::new("make".into(), None, i + 1);
+                entry.exit_status = Some(*code);
+                entry
+            })
+            .collect();
+
+        let signals = ErrorSignals::analyze(&entries);
+        // 3 failures out of 6 = 50%
+        assert_eq!(signals.score("failure_rate_high"), 1.0);
+    }
+
+    #[test]
+    fn command_not_found_counts_as_typo() {
+        let entries: Vec<_> = (0..10)
+            .map(|i| {
+                let mut entry = HistoryEntry::new("kubectl".into(), None, i + 1);
+                entry.exit_status = Some(if i < 3 { 127 } else { 0 });
+                entry
+            })
+            .collect();
+
+        let signals = ErrorSignals::analyze(&entries);
+        assert!(signals.score("typo_rate_high") > 0.9);
+    }
+
+    #[test]
+    fn clean_exit_statuses_are_a_low_failure_rate() {
+        let entries: Vec<_> = (0..60)
+            .map(|i| {
+                let mut entry = HistoryEntry::new("cargo".into(), None, i + 1);
+                entry.exit_status = Some(if i == 0 { 101 } else { 0 });
+                entry
+            })
+            .collect();
+
+        let signals = ErrorSignals::analyze(&entries);
+        assert!(signals.score("failure_rate_low") > 0.6);
+        assert!(signals.get("failure_rate_high").is_none());
+    }
+
+    #[test]
+    fn no_failure_rate_without_exit_status() {
+        let entries: Vec<_> = (0..10)
+            .map(|i| HistoryEntry::new("make".into(), None, i + 1))
+            .collect();
+
+        let signals = ErrorSignals::analyze(&entries);
+        assert!(signals.get("failure_rate_high").is_none());
+    }
+
     #[test]
     fn status_check_loop() {
         let entries: Vec<_> = ["git", "ls", "git", "ls", "git", "pwd", "git"]
@@ -199,5 +295,19 @@ mod tests {
         let signals = ErrorSignals::analyze(&entries);
         assert!(signals.score("status_check_loop") > 0.5);
     }
-}
 
+    #[test]
+    fn typo_followed_by_intended_command_is_a_correction() {
+        let dictionary = CommandDictionary::with_commands(["git", "ls"]);
+        let mut entries: Vec<_> = ["gti", "git status", "ls"]
+            .iter()
+            .enumerate()
+            .map(|(i, cmd)| HistoryEntry::new(cmd.to_string(), None, i + 1))
+            .collect();
+        dictionary.check_all(&mut entries);
+
+        // Too different as strings, but "gti" was meant to be "git"
+        assert!(!ErrorSignals::is_likely_correction("gti", "git status"));
+        assert_eq!(ErrorSignals::detect_corrections(&entries), 0.5);
+    }
+}
@@ -1,6 +1,9 @@
 //! Frequency-based signal detection.
 //!
 //! Analyzes command cadence and patterns over time.
+//!
+//! Recordings with keystrokes time each command from its first key to
+//! Enter, so bursts measure the pause before typing, not the typing.
 
 use super::{Signal, SignalCollection};
 use crate::history::HistoryEntry;
@@ -17,6 +20,12 @@ impl FrequencySignals {
             return signals;
         }
 
+        // Cadence, bursts and rhythm are all measured in time
+        if entries.iter().all(|e| e.timestamp.is_none()) {
+            signals.mark_unavailable("frequency", "history has no timestamps");
+            return signals;
+        }
+
         // Calculate commands per hour
         let cph = Self::commands_per_hour(entries);
 
@@ -75,10 +84,13 @@ impl FrequencySignals {
 
     /// Detect burst patterns (clusters of rapid commands).
     fn detect_bursts(entries: &[HistoryEntry]) -> f64 {
-        let mut timestamps: Vec<_> = entries.iter().filter_map(|e| e.timestamp).collect();
-        timestamps.sort();
+        let mut timed: Vec<_> = entries
+            .iter()
+            .filter_map(|e| e.timestamp.map(|ts| (ts, e.typing_time())))
+            .collect();
+        timed.sort_by_key(|&(ts, _)| ts);
 
-        if timestamps.len() < 3 {
+        if timed.len() < 3 {
             return 0.0;
         }
 
@@ -86,8 +98,9 @@ impl FrequencySignals {
         let mut burst_count = 0;
         let mut total_intervals = 0;
 
-        for window in timestamps.windows(2) {
-            let interval = (window[1] - window[0]).as_seconds_f64();
+        for window in timed.windows(2) {
+            let ((previous, _), (ts, typing)) = (window[0], window[1]);
+            let interval = (ts - previous - typing).as_seconds_f64();
             total_intervals += 1;
 
             if interval < 10.0 {
@@ -167,6 +180,19 @@ mod tests {
         assert!(signals.score("cadence_low") > 0.3);
     }
 
+    #[test]
+    fn typing_time_is_not_a_pause() {
+        // Commands 12 seconds apart, each typed for 10 of them
+        let mut entries = make_entries_with_interval(10, 12);
+        assert!(FrequencySignals::analyze(&entries).get("burst_pattern").is_none());
+
+        for entry in &mut entries {
+            entry.keystroke_gaps = vec![Duration::seconds(1); 10];
+        }
+        let signals = FrequencySignals::analyze(&entries);
+        assert!(signals.score("burst_pattern") > 0.9);
+    }
+
     #[test]
     fn steady_rhythm_detected() {
         // Commands every 30 seconds (very steady)
@@ -3,15 +3,27 @@
 //! Signals are patterns detected in shell history that contribute
 //! to mood classification. Each signal has an ID and a score (0.0 - 1.0).
 
+pub mod circadian;
+pub mod complexity;
 pub mod frequency;
 pub mod temporal;
 pub mod errors;
 pub mod diversity;
+pub mod duration;
+pub mod projects;
+pub mod rituals;
+pub mod trends;
 
+pub use circadian::CircadianSignals;
 pub use frequency::FrequencySignals;
 pub use temporal::TemporalSignals;
 pub use errors::ErrorSignals;
+pub use complexity::ComplexitySignals;
 pub use diversity::DiversitySignals;
+pub use duration::DurationSignals;
+pub use projects::ProjectSignals;
+pub use rituals::RitualSignals;
+pub use trends::TrendSignals;
 
 use crate::history::HistoryEntry;
 use serde::{Deserialize, Serialize};
@@ -56,10 +68,22 @@ impl Signal {
     }
 }
 
+/// A signal family that could not be computed from the history at hand.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Unavailable {
+    /// Signal family (e.g., "temporal")
+    pub family: String,
+    /// Why it could not be computed
+    pub reason: String,
+}
+
 /// Collection of all detected signals.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SignalCollection {
     pub signals: Vec<Signal>,
+    /// Signal families that were skipped, rather than found empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailable: Vec<Unavailable>,
 }
 
 impl SignalCollection {
@@ -81,6 +105,20 @@ impl SignalCollection {
         }
     }
 
+    /// Record that a whole signal family could not be computed.
+    pub fn mark_unavailable(&mut self, family: impl Into<String>, reason: impl Into<String>) {
+        self.unavailable.push(Unavailable {
+            family: family.into(),
+            reason: reason.into(),
+        });
+    }
+
+    /// Check whether a signal family was computed.
+    #[cfg(test)]
+    pub fn is_available(&self, family: &str) -> bool {
+        !self.unavailable.iter().any(|u| u.family == family)
+    }
+
     /// Get a signal by ID.
     pub fn get(&self, id: &str) -> Option<&Signal> {
         self.signals.iter().find(|s| s.id == id)
@@ -99,6 +137,7 @@ impl SignalCollection {
     /// Merge another collection into this one.
     pub fn merge(&mut self, other: SignalCollection) {
         self.signals.extend(other.signals);
+        self.unavailable.extend(other.unavailable);
     }
 }
 
@@ -122,6 +161,35 @@ pub fn analyze(entries: &[HistoryEntry]) -> SignalCollection {
     // Diversity signals
     signals.merge(DiversitySignals::analyze(entries));
 
+    // Duration signals
+    signals.merge(DurationSignals::analyze(entries));
+
+    // Command-line complexity signals
+    signals.merge(ComplexitySignals::analyze(entries));
+
+    // Project signals
+    signals.merge(ProjectSignals::analyze(entries));
+
+    // Ritual sequence signals
+    signals.merge(RitualSignals::analyze(entries));
+
+    // Rest and schedule signals
+    signals.merge(CircadianSignals::analyze(entries));
+
+    signals
+}
+
+/// Analyze history entries, and how they changed since the window before.
+///
+/// `previous` holds the entries from the window of equal length that
+/// ends where `entries` begin.
+pub fn analyze_with_previous(entries: &[HistoryEntry], previous: &[HistoryEntry]) -> SignalCollection {
+    let mut signals = analyze(entries);
+
+    if !entries.is_empty() {
+        signals.merge(TrendSignals::analyze(entries, previous));
+    }
+
     signals
 }
 
@@ -146,5 +214,40 @@ mod tests {
         assert_eq!(collection.score("test"), 0.8);
         assert_eq!(collection.score("missing"), 0.0);
     }
+
+    #[test]
+    fn untimed_history_marks_time_signals_unavailable() {
+        let entries: Vec<_> = (1..=30)
+            .map(|i| HistoryEntry::new("git".to_string(), None, i))
+            .collect();
+
+        let signals = analyze(&entries);
+        assert!(!signals.is_available("temporal"));
+        assert!(!signals.is_available("frequency"));
+        assert!(signals.is_available("diversity"));
+        assert!(signals.get("late_night_orbit").is_none());
+    }
+
+    #[test]
+    fn bare_history_marks_families_unavailable() {
+        // No timestamps, durations, shapes or working directories
+        let entries: Vec<_> = (1..=20)
+            .map(|i| HistoryEntry::new("ls".to_string(), None, i))
+            .collect();
+
+        type Analyze = fn(&[HistoryEntry]) -> SignalCollection;
+        let families: [(&str, Analyze); 4] = [
+            ("duration", DurationSignals::analyze),
+            ("complexity", ComplexitySignals::analyze),
+            ("projects", ProjectSignals::analyze),
+            ("circadian", CircadianSignals::analyze),
+        ];
+
+        for (family, analyze) in families {
+            let signals = analyze(&entries);
+            assert!(signals.signals.is_empty(), "{}", family);
+            assert!(!signals.is_available(family), "{}", family);
+        }
+    }
 }
 
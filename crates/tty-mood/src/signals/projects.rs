@@ -0,0 +1,185 @@
+//! Project signal detection.
+//!
+//! Analyzes how work moves between projects: hopping between five
+//! repositories in an hour, or staying inside one for the afternoon.
+//!
+//! Works from each entry's project fingerprint (see
+//! [`crate::history::projects`]). Visits are followed per shell, so two
+//! terminals open in two projects are not a switch every command. Only
+//! counts end up in the signature, never which projects they were.
+
+use super::{Signal, SignalCollection};
+use crate::history::{session, HistoryEntry};
+use std::collections::HashSet;
+use time::Duration;
+
+/// Minimum entries with a known project before projects say anything.
+const MIN_TAGGED: usize = 20;
+
+/// Pauses longer than this don't count as time spent in a project.
+const IDLE_GAP: Duration = Duration::minutes(30);
+
+/// Median time per visit from which work counts as deep.
+const DEEP_DWELL: Duration = Duration::minutes(45);
+
+/// Project signal analyzer.
+pub struct ProjectSignals;
+
+impl ProjectSignals {
+    /// Analyze project switches and dwell time in history entries.
+    pub fn analyze(entries: &[HistoryEntry]) -> SignalCollection {
+        let mut signals = SignalCollection::new();
+
+        let tagged: Vec<&HistoryEntry> = entries.iter().filter(|e| e.project.is_some()).collect();
+
+        if tagged.is_empty() {
+            if !entries.is_empty() {
+                signals.mark_unavailable("projects", "no working directories could be resolved");
+            }
+            return signals;
+        }
+
+        if tagged.len() < MIN_TAGGED {
+            return signals;
+        }
+
+        let shells = session::by_shell(entries);
+        let visits: Vec<Vec<&HistoryEntry>> = shells.iter().flat_map(|s| Self::visits(s)).collect();
+        let projects: HashSet<u64> = tagged.iter().filter_map(|e| e.project).collect();
+
+        // Leaving one project for another, often: each shell's first
+        // visit is no switch
+        let opened = shells
+            .iter()
+            .filter(|s| s.iter().any(|e| e.project.is_some()))
+            .count();
+        let switches = visits.len() - opened;
+        let switch_ratio = switches as f64 / (tagged.len() - opened).max(1) as f64;
+        if projects.len() >= 3 && switch_ratio > 0.05 {
+            signals.add(
+                Signal::new("project_hopping", (switch_ratio * 5.0).min(1.0)).with_note(format!(
+                    "{} switches between {} projects",
+                    switches,
+                    projects.len()
+                )),
+            );
+        }
+
+        // Long, uninterrupted stays
+        let mut dwells: Vec<Duration> = visits.iter().filter_map(|v| Self::dwell(v)).collect();
+        if !dwells.is_empty() {
+            dwells.sort();
+            let median = dwells[dwells.len() / 2];
+            if median >= DEEP_DWELL {
+                signals.add(
+                    Signal::new(
+                        "deep_dwell",
+                        (median.whole_minutes() as f64 / 120.0).min(1.0),
+                    )
+                    .with_note(format!(
+                        "{} minutes per project visit (median of {})",
+                        median.whole_minutes(),
+                        dwells.len()
+                    )),
+                );
+            }
+        }
+
+        signals
+    }
+
+    /// Split one shell's entries into visits: runs in the same project.
+    fn visits<'a>(shell: &[&'a HistoryEntry]) -> Vec<Vec<&'a HistoryEntry>> {
+        let mut visits: Vec<Vec<&HistoryEntry>> = Vec::new();
+        for entry in shell.iter().filter(|e| e.project.is_some()) {
+            match visits.last_mut() {
+                Some(visit) if visit[0].project == entry.project => visit.push(entry),
+                _ => visits.push(vec![entry]),
+            }
+        }
+        visits
+    }
+
+    /// Active time spent in a visit, or None without timestamps.
+    ///
+    /// Pauses longer than [`IDLE_GAP`] are left out, so a visit spanning
+    /// lunch doesn't count the lunch.
+    fn dwell(visit: &[&HistoryEntry]) -> Option<Duration> {
+        let timestamps: Vec<_> = visit.iter().filter_map(|e| e.timestamp).collect();
+        if timestamps.is_empty() {
+            return None;
+        }
+
+        Some(
+            timestamps
+                .windows(2)
+                .map(|pair| pair[1] - pair[0])
+                .filter(|gap| !gap.is_negative() && *gap <= IDLE_GAP)
+                .sum(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::OffsetDateTime;
+
+    /// Entries in the given projects, `minutes` apart.
+    fn entries(projects: &[u64], minutes: i64) -> Vec<HistoryEntry> {
+        let start = OffsetDateTime::from_unix_timestamp(1_702_400_000).unwrap();
+        projects
+            .iter()
+            .enumerate()
+            .map(|(i, project)| {
+                let ts = start + Duration::minutes(minutes * i as i64);
+                let mut entry = HistoryEntry::new("ls".into(), Some(ts), i + 1);
+                entry.project = Some(*project);
+                entry
+            })
+            .collect()
+    }
+
+    #[test]
+    fn switching_projects_is_hopping() {
+        let projects: Vec<u64> = [1, 1, 2, 3, 3, 1, 4].repeat(4);
+        let signals = ProjectSignals::analyze(&entries(&projects, 1));
+
+        let hopping = signals.get("project_hopping").unwrap();
+        assert_eq!(hopping.score, 1.0);
+        assert_eq!(
+            hopping.note.as_deref(),
+            Some("19 switches between 4 projects")
+        );
+        assert!(signals.get("deep_dwell").is_none());
+    }
+
+    #[test]
+    fn long_stays_are_deep_dwell() {
+        let mut projects = vec![1; 15];
+        projects.extend([2; 15]);
+        let signals = ProjectSignals::analyze(&entries(&projects, 5));
+
+        // 14 gaps of 5 minutes per visit
+        let dwell = signals.get("deep_dwell").unwrap();
+        assert!((dwell.score - 70.0 / 120.0).abs() < 1e-9);
+        assert!(signals.get("project_hopping").is_none());
+
+        // Hour-long pauses don't count
+        let signals = ProjectSignals::analyze(&entries(&projects, 60));
+        assert!(signals.get("deep_dwell").is_none());
+    }
+
+    #[test]
+    fn terminals_in_different_projects_are_not_hopping() {
+        // Two terminals, one per project, commands interleaved
+        let mut entries = entries(&[1, 2, 3].repeat(10), 5);
+        for entry in &mut entries {
+            entry.session = entry.project.map(|p| p.to_string());
+        }
+
+        let signals = ProjectSignals::analyze(&entries);
+        assert!(signals.get("project_hopping").is_none());
+        assert!(signals.score("deep_dwell") > 0.9);
+    }
+}
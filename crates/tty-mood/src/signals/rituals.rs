@@ -0,0 +1,234 @@
+//! Ritual sequence detection.
+//!
+//! Finds ordered sequences of commands that come back again and again:
+//! `git add → git commit → git push`, or `cargo fmt → cargo clippy →
+//! cargo test`. Filling in the same form, every time.
+//!
+//! Every invoked command is a step, so `git add . && git commit &&
+//! git push` typed on one line is the same ritual as three lines.
+//! Steps are compared by their hashes (the command and, for tools like
+//! git or cargo, its subcommand), so no command text is needed.
+//! Sequences are looked for within each shell session, not across
+//! interleaved terminals. The signature only learns how many rituals
+//! there are.
+
+use super::{Signal, SignalCollection};
+use crate::history::{session, HistoryEntry};
+use std::collections::{HashMap, HashSet};
+use time::{Duration, OffsetDateTime};
+
+/// Steps in a ritual.
+const RITUAL_LENGTH: usize = 3;
+
+/// Times a sequence has to recur before it is a ritual.
+const MIN_REPEATS: usize = 3;
+
+/// Minimum entries before rituals say anything.
+const MIN_ENTRIES: usize = 20;
+
+/// A ritual is performed in one sitting: longer pauses break it.
+const IDLE_GAP: Duration = Duration::minutes(30);
+
+/// Ritual signal analyzer.
+pub struct RitualSignals;
+
+/// One step: its hash, and when the line it was typed on ran.
+type Step = (u64, Option<OffsetDateTime>);
+
+impl RitualSignals {
+    /// Analyze recurring command sequences in history entries.
+    pub fn analyze(entries: &[HistoryEntry]) -> SignalCollection {
+        let mut signals = SignalCollection::new();
+
+        if entries.len() < MIN_ENTRIES {
+            return signals;
+        }
+
+        // Each shell's steps, in order
+        let shells: Vec<Vec<Step>> = session::by_shell(entries)
+            .into_iter()
+            .map(|shell| {
+                shell
+                    .into_iter()
+                    .flat_map(|e| e.steps.iter().map(|step| (*step, e.timestamp)))
+                    .collect()
+            })
+            .collect();
+        let total: usize = shells.iter().map(Vec::len).sum();
+
+        // How often each sequence of steps occurs
+        let mut occurrences: HashMap<[u64; RITUAL_LENGTH], usize> = HashMap::new();
+        for window in shells.iter().flat_map(|s| s.windows(RITUAL_LENGTH)) {
+            if Self::is_candidate(window) {
+                let steps = std::array::from_fn(|i| window[i].0);
+                *occurrences.entry(steps).or_default() += 1;
+            }
+        }
+
+        let rituals: HashSet<[u64; RITUAL_LENGTH]> = occurrences
+            .into_iter()
+            .filter(|(_, count)| *count >= MIN_REPEATS)
+            .map(|(steps, _)| steps)
+            .collect();
+
+        if rituals.is_empty() {
+            return signals;
+        }
+
+        // Walk each shell taking whole rituals, so that overlapping
+        // sequences (commit → push → fmt between two rituals) don't
+        // count on their own
+        let mut performed = Vec::new();
+        for shell in &shells {
+            let mut start = 0;
+            while start + RITUAL_LENGTH <= shell.len() {
+                let window = &shell[start..start + RITUAL_LENGTH];
+                let steps = std::array::from_fn(|i| window[i].0);
+                if rituals.contains(&steps) && Self::is_candidate(window) {
+                    performed.push(steps);
+                    start += RITUAL_LENGTH;
+                } else {
+                    start += 1;
+                }
+            }
+        }
+
+        // A ritual repeated back to back also repeats its rotations:
+        // add → commit → push → add → commit → push contains
+        // commit → push → add. Those count once.
+        let distinct: HashSet<[u64; RITUAL_LENGTH]> = performed
+            .iter()
+            .map(|steps| Self::canonical(*steps))
+            .collect();
+
+        // Share of history spent performing rituals
+        let share = (performed.len() * RITUAL_LENGTH) as f64 / total as f64;
+
+        if share > 0.1 {
+            signals.add(
+                Signal::new("ritual_sequence", (share * 2.0).min(1.0)).with_note(format!(
+                    "{} distinct rituals, performed {} times",
+                    distinct.len(),
+                    performed.len()
+                )),
+            );
+        }
+
+        signals
+    }
+
+    /// Whether a window of steps can be a ritual.
+    ///
+    /// The same step over and over is repetition, not ritual, and a
+    /// long pause between two steps means the sequence was accidental.
+    fn is_candidate(window: &[Step]) -> bool {
+        let steps: HashSet<u64> = window.iter().map(|(step, _)| *step).collect();
+        if steps.len() < 2 {
+            return false;
+        }
+
+        window.windows(2).all(|pair| match (pair[0].1, pair[1].1) {
+            (Some(a), Some(b)) => b - a <= IDLE_GAP,
+            _ => true,
+        })
+    }
+
+    /// The smallest rotation of a sequence, identifying it with its rotations.
+    fn canonical(steps: [u64; RITUAL_LENGTH]) -> [u64; RITUAL_LENGTH] {
+        (0..RITUAL_LENGTH)
+            .map(|shift| {
+                let mut rotated = steps;
+                rotated.rotate_left(shift);
+                rotated
+            })
+            .min()
+            .unwrap_or(steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(lines: &[&str]) -> Vec<HistoryEntry> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| HistoryEntry::from_command_line(line, None, i + 1))
+            .collect()
+    }
+
+    #[test]
+    fn recurring_sequences_are_rituals() {
+        let mut lines = Vec::new();
+        for i in 0..4 {
+            lines.extend(["git add -p", "git commit -m wip", "git push"]);
+            lines.extend(["cargo fmt", "cargo clippy", "cargo test"]);
+            lines.push(["vim a.rs", "ls", "htop", "man tar"][i]);
+        }
+
+        let signals = RitualSignals::analyze(&entries(&lines));
+        let ritual = signals.get("ritual_sequence").unwrap();
+        assert!(ritual.score > 0.9);
+        assert_eq!(
+            ritual.note.as_deref(),
+            Some("2 distinct rituals, performed 8 times")
+        );
+    }
+
+    #[test]
+    fn back_to_back_repeats_count_once() {
+        let lines = ["git add .", "git commit", "git push"].repeat(8);
+
+        let signals = RitualSignals::analyze(&entries(&lines));
+        let ritual = signals.get("ritual_sequence").unwrap();
+        assert_eq!(ritual.score, 1.0);
+        assert_eq!(
+            ritual.note.as_deref(),
+            Some("1 distinct rituals, performed 8 times")
+        );
+    }
+
+    #[test]
+    fn sequences_stay_within_one_shell() {
+        // add → commit → push in one terminal, a build loop in another
+        let mut lines = Vec::new();
+        for _ in 0..5 {
+            lines.extend(["git add .", "make", "git commit", "make"]);
+            lines.extend(["git push", "make"]);
+        }
+
+        let mut entries = entries(&lines);
+        for (i, entry) in entries.iter_mut().enumerate() {
+            entry.session = Some((i % 2).to_string());
+        }
+        let signals = RitualSignals::analyze(&entries);
+        assert_eq!(
+            signals.get("ritual_sequence").unwrap().note.as_deref(),
+            Some("1 distinct rituals, performed 5 times")
+        );
+    }
+
+    #[test]
+    fn chained_lines_are_rituals_too() {
+        let mut lines = vec!["git add . && git commit -m wip && git push"; 4];
+        lines.extend(["git add .", "git commit", "git push"]);
+        lines.extend(["vim", "ls", "htop", "man tar", "make", "ssh", "df", "du"]);
+        lines.extend(["top", "ping", "curl", "tar", "less"]);
+
+        let signals = RitualSignals::analyze(&entries(&lines));
+        assert_eq!(
+            signals.get("ritual_sequence").unwrap().note.as_deref(),
+            Some("1 distinct rituals, performed 5 times")
+        );
+    }
+
+    #[test]
+    fn repetition_and_noise_are_not_rituals() {
+        let mut lines = vec!["ls"; 15];
+        lines.extend(["vim", "git status", "cargo build", "make", "htop", "ssh"]);
+
+        let signals = RitualSignals::analyze(&entries(&lines));
+        assert!(signals.get("ritual_sequence").is_none());
+    }
+}
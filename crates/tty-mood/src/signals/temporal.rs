@@ -20,6 +20,7 @@ impl TemporalSignals {
         let with_timestamps: Vec<_> = entries.iter().filter(|e| e.timestamp.is_some()).collect();
 
         if with_timestamps.is_empty() {
+            signals.mark_unavailable("temporal", "history has no timestamps");
             return signals;
         }
 
@@ -0,0 +1,179 @@
+//! Trend signal detection.
+//!
+//! A high typo rate over seven days says little on its own; a typo
+//! rate that just doubled says more. Compares the analyzed range with
+//! the window of equal length right before it and reports what moved:
+//! cadence, typo rate and the share of late-night commands.
+//!
+//! Only entries with timestamps count, in both windows, so the two are
+//! measured the same way.
+
+use super::{ErrorSignals, Signal, SignalCollection};
+use crate::history::HistoryEntry;
+
+/// Minimum timed entries in each window before trends say anything.
+const MIN_WINDOW: usize = 20;
+
+/// Trend signal analyzer.
+pub struct TrendSignals;
+
+/// What is compared between the two windows.
+struct Window {
+    commands: usize,
+    typo_rate: f64,
+    night_share: f64,
+}
+
+impl Window {
+    fn measure(entries: &[HistoryEntry]) -> Self {
+        let timed: Vec<&HistoryEntry> = entries.iter().filter(|e| e.timestamp.is_some()).collect();
+        let total = timed.len().max(1) as f64;
+        let typos = timed.iter().filter(|e| ErrorSignals::is_typo(e)).count();
+        let night = timed.iter().filter(|e| e.is_late_night()).count();
+
+        Self {
+            commands: timed.len(),
+            typo_rate: typos as f64 / total,
+            night_share: night as f64 / total,
+        }
+    }
+}
+
+impl TrendSignals {
+    /// Compare entries with those from the preceding window.
+    pub fn analyze(entries: &[HistoryEntry], previous: &[HistoryEntry]) -> SignalCollection {
+        let mut signals = SignalCollection::new();
+
+        if previous.is_empty() {
+            signals.mark_unavailable("trends", "history doesn't reach back before the range");
+            return signals;
+        }
+
+        let now = Window::measure(entries);
+        let before = Window::measure(previous);
+
+        if now.commands < MIN_WINDOW || before.commands < MIN_WINDOW {
+            return signals;
+        }
+
+        // Cadence: both windows are equally long, so counts compare directly
+        let ratio = now.commands as f64 / before.commands as f64;
+        if ratio >= 1.5 {
+            signals.add(
+                Signal::new("cadence_rising", ((ratio - 1.0) / 2.0).min(1.0)).with_note(format!(
+                    "{} commands, up from {}",
+                    now.commands, before.commands
+                )),
+            );
+        } else if ratio <= 1.0 / 1.5 {
+            signals.add(
+                Signal::new("cadence_falling", ((1.0 / ratio - 1.0) / 2.0).min(1.0)).with_note(
+                    format!("{} commands, down from {}", now.commands, before.commands),
+                ),
+            );
+        }
+
+        // Typo rate, once the change is more than noise
+        let typo_change = now.typo_rate - before.typo_rate;
+        let typo_note = |direction: &str| {
+            format!(
+                "{}% possible typos, {} from {}%",
+                (now.typo_rate * 100.0) as u32,
+                direction,
+                (before.typo_rate * 100.0) as u32
+            )
+        };
+        if typo_change > 0.03 && now.typo_rate >= before.typo_rate * 1.5 {
+            signals.add(
+                Signal::new("typo_rate_rising", (typo_change * 10.0).min(1.0))
+                    .with_note(typo_note("up")),
+            );
+        } else if typo_change < -0.03 && before.typo_rate >= now.typo_rate * 1.5 {
+            signals.add(
+                Signal::new("typo_rate_falling", (-typo_change * 10.0).min(1.0))
+                    .with_note(typo_note("down")),
+            );
+        }
+
+        // Work moving into the night, or back out of it
+        let night_change = now.night_share - before.night_share;
+        let night_note = |direction: &str| {
+            format!(
+                "{}% of commands after 22:00, {} from {}%",
+                (now.night_share * 100.0) as u32,
+                direction,
+                (before.night_share * 100.0) as u32
+            )
+        };
+        if night_change > 0.1 {
+            signals.add(
+                Signal::new("night_shift_creeping", (night_change * 3.0).min(1.0))
+                    .with_note(night_note("up")),
+            );
+        } else if night_change < -0.1 {
+            signals.add(
+                Signal::new("night_shift_receding", (-night_change * 3.0).min(1.0))
+                    .with_note(night_note("down")),
+            );
+        }
+
+        signals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Date, Month, PrimitiveDateTime, Time};
+
+    /// `count` commands at `hour`, every `typo_every`th one not found.
+    fn window(count: usize, hour: u8, typo_every: usize) -> Vec<HistoryEntry> {
+        let date = Date::from_calendar_date(2025, Month::December, 10).unwrap();
+        (0..count)
+            .map(|i| {
+                let ts = PrimitiveDateTime::new(date, Time::from_hms(hour, 0, 0).unwrap());
+                let mut entry = HistoryEntry::new("cargo".into(), Some(ts.assume_utc()), i + 1);
+                entry.exit_status = Some(if i % typo_every == 0 { 127 } else { 0 });
+                entry
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rising_cadence_and_typos() {
+        let before = window(40, 14, 40);
+        let now = window(100, 14, 5);
+
+        let signals = TrendSignals::analyze(&now, &before);
+        let cadence = signals.get("cadence_rising").unwrap();
+        assert_eq!(cadence.score, 0.75);
+        assert_eq!(cadence.note.as_deref(), Some("100 commands, up from 40"));
+        assert_eq!(
+            signals.get("typo_rate_rising").unwrap().note.as_deref(),
+            Some("20% possible typos, up from 2%")
+        );
+        assert!(signals.get("typo_rate_falling").is_none());
+    }
+
+    #[test]
+    fn night_shift_creeps_in() {
+        let mut now = window(30, 14, 100);
+        now.extend(window(30, 23, 100));
+        let before = window(60, 14, 100);
+
+        let signals = TrendSignals::analyze(&now, &before);
+        assert_eq!(signals.score("night_shift_creeping"), 1.0);
+        assert!(signals.get("cadence_rising").is_none());
+        assert!(signals.get("cadence_falling").is_none());
+
+        let signals = TrendSignals::analyze(&before, &now);
+        assert_eq!(signals.score("night_shift_receding"), 1.0);
+    }
+
+    #[test]
+    fn no_previous_window_is_unavailable() {
+        let signals = TrendSignals::analyze(&window(50, 14, 10), &[]);
+        assert!(signals.signals.is_empty());
+        assert!(!signals.is_available("trends"));
+    }
+}
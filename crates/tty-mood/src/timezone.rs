@@ -0,0 +1,175 @@
+//! Local time zone handling.
+//!
+//! History files store instants (Unix time), which parsers read as UTC.
+//! Temporal signals care about wall-clock hours, so timestamps are moved
+//! into the user's zone before analysis. The offset is looked up for
+//! each timestamp, so entries on either side of a DST change both land
+//! on the right hour.
+//!
+//! The zone comes from `--tz`, else `$TZ`, else `/etc/localtime`, read
+//! from the system zoneinfo database.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
+
+/// A time zone with the name it was resolved from.
+#[derive(Debug, Clone)]
+pub struct LocalZone {
+    name: String,
+    zone: tz::TimeZone,
+}
+
+impl LocalZone {
+    /// Resolve the zone to use: an explicit name, or the system zone.
+    pub fn resolve(name: Option<&str>) -> Result<Self> {
+        match name {
+            Some(name) => Self::named(name),
+            None => Ok(Self::system()),
+        }
+    }
+
+    /// Load a zone by IANA name (`Europe/Berlin`) or POSIX TZ string
+    /// (`CET-1CEST,M3.5.0,M10.5.0/3`).
+    pub fn named(name: &str) -> Result<Self> {
+        let zone = tz::TimeZone::from_posix_tz(name)
+            .with_context(|| format!("Unknown time zone: {}", name))?;
+
+        Ok(Self {
+            name: name.trim_start_matches(':').to_string(),
+            zone,
+        })
+    }
+
+    /// The system zone, falling back to UTC if it can't be read.
+    pub fn system() -> Self {
+        if let Some(name) = std::env::var("TZ").ok().filter(|tz| !tz.is_empty()) {
+            if let Ok(zone) = Self::named(&name) {
+                return zone;
+            }
+        }
+
+        match tz::TimeZone::local() {
+            Ok(zone) => Self {
+                name: system_zone_name(),
+                zone,
+            },
+            Err(_) => Self::utc(),
+        }
+    }
+
+    /// Coordinated Universal Time.
+    pub fn utc() -> Self {
+        Self {
+            name: "UTC".to_string(),
+            zone: tz::TimeZone::utc(),
+        }
+    }
+
+    /// The zone's name, as recorded in the signature.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The zone's UTC offset at an instant (DST-aware).
+    pub fn offset_at(&self, instant: OffsetDateTime) -> UtcOffset {
+        self.zone
+            .find_local_time_type(instant.unix_timestamp())
+            .ok()
+            .and_then(|local| UtcOffset::from_whole_seconds(local.ut_offset()).ok())
+            .unwrap_or(UtcOffset::UTC)
+    }
+
+    /// Express an instant in this zone's wall-clock time.
+    pub fn to_local(&self, instant: OffsetDateTime) -> OffsetDateTime {
+        instant.to_offset(self.offset_at(instant))
+    }
+
+    /// The instant a wall-clock time in this zone refers to.
+    ///
+    /// Times skipped when clocks go forward land just after the gap.
+    pub fn instant_at(&self, local: PrimitiveDateTime) -> OffsetDateTime {
+        let guess = local.assume_offset(self.offset_at(local.assume_utc()));
+        local.assume_offset(self.offset_at(guess))
+    }
+}
+
+/// Name of the system zone, e.g. `Europe/Berlin`.
+///
+/// `/etc/localtime` is usually a symlink into the zoneinfo database;
+/// Debian-style systems also write the name to `/etc/timezone`.
+fn system_zone_name() -> String {
+    if let Ok(target) = std::fs::read_link("/etc/localtime") {
+        if let Some((_, name)) = target.to_string_lossy().split_once("zoneinfo/") {
+            return name.to_string();
+        }
+    }
+
+    std::fs::read_to_string(Path::new("/etc/timezone"))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localtime".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Central European Time, with the EU DST rules.
+    const BERLIN: &str = "CET-1CEST,M3.5.0,M10.5.0/3";
+
+    fn at(unix: i64) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(unix).unwrap()
+    }
+
+    #[test]
+    fn offsets_follow_dst() {
+        let zone = LocalZone::named(BERLIN).unwrap();
+
+        // 2023-01-15 23:30 UTC is 00:30 the next day in Berlin
+        let winter = zone.to_local(at(1673825400));
+        assert_eq!(winter.offset().whole_hours(), 1);
+        assert_eq!(winter.hour(), 0);
+        assert_eq!(winter.day(), 16);
+
+        // 2023-07-15 21:30 UTC is 23:30 in Berlin
+        let summer = zone.to_local(at(1689456600));
+        assert_eq!(summer.offset().whole_hours(), 2);
+        assert_eq!(summer.hour(), 23);
+    }
+
+    #[test]
+    fn dst_switch_changes_offset_mid_night() {
+        let zone = LocalZone::named(BERLIN).unwrap();
+
+        // Clocks go forward at 01:00 UTC on 2023-03-26
+        assert_eq!(zone.offset_at(at(1679792400 - 60)).whole_hours(), 1);
+        assert_eq!(zone.offset_at(at(1679792400)).whole_hours(), 2);
+    }
+
+    #[test]
+    fn local_times_map_back_to_instants() {
+        let zone = LocalZone::named(BERLIN).unwrap();
+        let winter = time::macros::datetime!(2023-01-16 00:30);
+        let summer = time::macros::datetime!(2023-07-15 23:30);
+
+        assert_eq!(zone.instant_at(winter), at(1673825400));
+        assert_eq!(zone.instant_at(summer), at(1689456600));
+    }
+
+    #[test]
+    fn utc_keeps_instants() {
+        let zone = LocalZone::utc();
+        let local = zone.to_local(at(1702400000));
+        assert_eq!(local, at(1702400000));
+        assert_eq!(local.offset(), UtcOffset::UTC);
+        assert_eq!(zone.name(), "UTC");
+    }
+
+    #[test]
+    fn unknown_zones_are_errors() {
+        assert!(LocalZone::named("Mars/Olympus_Mons").is_err());
+        assert!(LocalZone::named("").is_err());
+    }
+}